@@ -0,0 +1,54 @@
+//! A module to handle the archive of the list in use
+//! from the command line.
+
+use todo::libs::config::Config;
+use todo::libs::render::theme::{paint, theme};
+use todo::libs::tasks::archive;
+use todo::libs::tasks::selection::{Selection, Status};
+
+use crate::batch::{choose, count_tasks, report_moved};
+
+/// `archive_commands` moves the selected tasks to the archive of the list in use,
+/// or the completed ones if none are selected.
+///
+/// # Example
+///
+/// ```
+/// archive_commands(Selection::default(), config::io::load());
+/// ```
+pub(crate) fn archive_commands(mut selection: Selection, config: Config) {
+    if selection.is_empty() {
+        selection.status = Some(Status::Completed);
+    }
+
+    let moved = archive::archive(&config, |tasks| choose(tasks, &selection, "Task"));
+
+    report_moved(&moved, "Archived");
+}
+
+/// `unarchive_commands` moves the selected archived tasks back to the list in use.
+///
+/// # Example
+///
+/// ```
+/// unarchive_commands(select.selection(), config::io::load());
+/// ```
+pub(crate) fn unarchive_commands(selection: Selection, config: Config) {
+    let moved = archive::unarchive(&config, |tasks| choose(tasks, &selection, "Archived task"));
+
+    report_moved(&moved, "Restored");
+}
+
+/// `auto_archive` archives the tasks completed long ago, if it is configured,
+/// telling how many were archived.
+pub(crate) fn auto_archive(config: &Config) {
+    let moved = archive::auto_archive(config);
+
+    if let (false, Some(days)) = (moved.is_empty(), config.get_archive_after_days()) {
+        eprintln!(
+            "{} : Archived {} completed more than {days} days ago.",
+            paint("INFO", &theme().info),
+            count_tasks(moved.len())
+        );
+    }
+}
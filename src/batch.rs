@@ -0,0 +1,347 @@
+//! A module to handle the commands changing several tasks at once,
+//! selected by numbers, ranges of numbers and filters, like `todo done 3 5 7-10`.
+//! All the changes of a command are saved together, or none of them if a task is missing.
+//! Removals are confirmed on the terminal first, unless `--force` is given.
+
+use clap::Args;
+use clap_complete::engine::ArgValueCandidates;
+use serde_json::json;
+use todo::libs::config::Config;
+use todo::libs::output::{code, TaskRecord};
+use todo::libs::tasks::selection::{Selection, Status, Target};
+use todo::libs::tasks::task::Task;
+use todo::libs::tasks::{NotFound, Tasks};
+use todo::libs::{output, tasks};
+
+use crate::completions::task_numbers;
+
+#[derive(Args)]
+#[group(id = "selection", required = true, multiple = true)]
+pub struct SelectArgs {
+    /// The task numbers, or ranges of them, like 3 5 7-10
+    #[arg(value_name = "NUMBERS", add = ArgValueCandidates::new(task_numbers))]
+    pub targets: Vec<Target>,
+
+    /// Select the completed tasks
+    #[arg(long, conflicts_with = "pending")]
+    pub completed: bool,
+
+    /// Select the tasks not completed yet
+    #[arg(long)]
+    pub pending: bool,
+
+    /// Select the tasks with this text in the name or the description, ignoring case
+    #[arg(long, value_name = "TEXT")]
+    pub matching: Option<String>,
+}
+
+/// `SelectArgs` is an implementation of the selection options.
+impl SelectArgs {
+    /// `selection` returns the selection the options describe.
+    pub fn selection(&self) -> Selection {
+        let status = match (self.completed, self.pending) {
+            (true, _) => Some(Status::Completed),
+            (_, true) => Some(Status::Pending),
+            _ => None,
+        };
+
+        Selection {
+            targets: self.targets.clone(),
+            status,
+            matching: self.matching.clone(),
+        }
+    }
+}
+
+/// `Batch` is the change made to every selected task.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Batch {
+    /// The tasks are removed.
+    Remove,
+    /// The tasks are completed, or reopened if they were.
+    Toggle,
+    /// The tasks are completed.
+    Done,
+}
+
+/// `Batch` is an implementation of the change.
+impl Batch {
+    /// `name` returns the name of the command making the change.
+    fn name(&self) -> &'static str {
+        match self {
+            Batch::Remove => "remove",
+            Batch::Toggle => "toggle",
+            Batch::Done => "done",
+        }
+    }
+
+    /// `verb` returns how the change is told in the summary.
+    fn verb(&self) -> &'static str {
+        match self {
+            Batch::Remove => "Removed",
+            Batch::Toggle => "Toggled",
+            Batch::Done => "Completed",
+        }
+    }
+}
+
+/// `count_tasks` returns a number of tasks as text, like `1 task` or `3 tasks`.
+pub(crate) fn count_tasks(count: usize) -> String {
+    match count {
+        1 => String::from("1 task"),
+        count => format!("{count} tasks"),
+    }
+}
+
+/// `Outcome` is what a change did, the changed tasks with their numbers
+/// and the numbers of the selected tasks that were already as asked.
+/// A cancelled change did nothing.
+pub struct Outcome {
+    batch: Batch,
+    changed: Vec<(usize, Task)>,
+    unchanged: Vec<usize>,
+    cancelled: bool,
+}
+
+/// `Outcome` is an implementation of the outcome.
+impl Outcome {
+    /// `cancelled` returns the outcome of a change that was not confirmed.
+    fn cancelled(batch: Batch) -> Outcome {
+        Outcome {
+            batch,
+            changed: vec![],
+            unchanged: vec![],
+            cancelled: true,
+        }
+    }
+
+    /// `summary` returns a line telling how many tasks were changed.
+    fn summary(&self) -> String {
+        if self.cancelled {
+            return String::from("Cancelled, nothing was changed.");
+        }
+
+        match (self.changed.len(), self.unchanged.len()) {
+            (0, 0) => String::from("No task selected."),
+            (changed, 0) => format!("{} {}.", self.batch.verb(), count_tasks(changed)),
+            (changed, unchanged) => format!(
+                "{} {}, {unchanged} already {}.",
+                self.batch.verb(),
+                count_tasks(changed),
+                if unchanged == 1 { "was" } else { "were" }
+            ),
+        }
+    }
+}
+
+/// `Selected` is the selected tasks with their numbers.
+type Selected = Vec<(usize, Task)>;
+
+/// `apply` makes a change to the selected tasks in memory, confirming it first like `confirm`.
+/// Nothing is changed if a selected number has no task.
+/// The error is the exit code and the message to fail with.
+pub fn apply(
+    tasks: &mut Tasks,
+    batch: Batch,
+    selection: &Selection,
+    force: bool,
+) -> Result<Outcome, (i32, String)> {
+    if confirm(tasks, batch, selection, force)?.is_none() {
+        return Ok(Outcome::cancelled(batch));
+    }
+
+    change(tasks, batch, selection)
+}
+
+/// `confirm` asks on the terminal to remove the selected tasks, showing them,
+/// and returns them with their numbers once confirmed, or None if cancelled.
+/// Other changes and forced removals are not asked for.
+/// Fails if there is no terminal to ask on and more than one task would be removed.
+fn confirm(
+    tasks: &Tasks,
+    batch: Batch,
+    selection: &Selection,
+    force: bool,
+) -> Result<Option<Selected>, (i32, String)> {
+    let selected = selected(tasks, selection)?;
+
+    if batch != Batch::Remove || force || selected.is_empty() {
+        return Ok(Some(selected));
+    }
+
+    let lines: Vec<String> = selected
+        .iter()
+        .map(|(index, task)| format!("{index} - {}", task.to_string_short()))
+        .collect();
+
+    match output::confirm(&lines, &format!("Remove {}?", count_tasks(selected.len()))) {
+        Some(true) => Ok(Some(selected)),
+        Some(false) => Ok(None),
+        None if selected.len() > 1 => Err((
+            code::INVALID_INPUT,
+            format!(
+                "This removes {}, add --force to confirm.",
+                count_tasks(selected.len())
+            ),
+        )),
+        None => Ok(Some(selected)),
+    }
+}
+
+/// `selected` returns the selected tasks with their numbers.
+fn selected(tasks: &Tasks, selection: &Selection) -> Result<Selected, (i32, String)> {
+    selection
+        .indexes(tasks)
+        .map_err(|number| not_found(NotFound(number)))?
+        .into_iter()
+        .map(|index| Ok((index, tasks.find(index).map_err(not_found)?.clone())))
+        .collect()
+}
+
+/// `change` makes a change to the selected tasks in memory, without asking.
+/// Nothing is changed if a selected number has no task.
+fn change(
+    tasks: &mut Tasks,
+    batch: Batch,
+    selection: &Selection,
+) -> Result<Outcome, (i32, String)> {
+    let indexes = selection
+        .indexes(tasks)
+        .map_err(|number| not_found(NotFound(number)))?;
+
+    let mut outcome = Outcome {
+        batch,
+        changed: vec![],
+        unchanged: vec![],
+        cancelled: false,
+    };
+
+    match batch {
+        Batch::Remove => outcome.changed = tasks.remove_many(&indexes).map_err(not_found)?,
+
+        Batch::Toggle => {
+            for index in indexes {
+                tasks.toggle_completed(index).map_err(not_found)?;
+                outcome
+                    .changed
+                    .push((index, tasks.find(index).map_err(not_found)?.clone()));
+            }
+        }
+
+        Batch::Done => {
+            for index in indexes {
+                if tasks.set_completed(index, true).map_err(not_found)? {
+                    outcome
+                        .changed
+                        .push((index, tasks.find(index).map_err(not_found)?.clone()));
+                } else {
+                    outcome.unchanged.push(index);
+                }
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// `not_found` returns the exit code and the message to fail with for a missing task.
+pub(crate) fn not_found(err: NotFound) -> (i32, String) {
+    (code::NOT_FOUND, err.to_string())
+}
+
+/// `report` writes the changed tasks, followed by a summary when there is not just one.
+pub fn report(outcome: &Outcome) {
+    let records: Vec<TaskRecord> = outcome
+        .changed
+        .iter()
+        .map(|(number, task)| TaskRecord::new(*number, task))
+        .collect();
+
+    let value = json!({
+        "action": outcome.batch.name(),
+        "tasks": records,
+        "unchanged": outcome.unchanged,
+        "cancelled": outcome.cancelled,
+    });
+
+    output::report(&value, || {
+        for (number, task) in &outcome.changed {
+            println!("{number} - {}", task.to_string_short());
+        }
+
+        if outcome.changed.len() != 1 || !outcome.unchanged.is_empty() || outcome.cancelled {
+            println!("{}", outcome.summary());
+        }
+    });
+}
+
+/// `choose` returns the numbers of the selected tasks,
+/// exiting with NOT_FOUND error code if a selected number has no task.
+pub(crate) fn choose(tasks: &Tasks, selection: &Selection, label: &str) -> Vec<usize> {
+    selection.indexes(tasks).unwrap_or_else(|number| {
+        output::fail(code::NOT_FOUND, format!("{label} {number} not found."))
+    })
+}
+
+/// `report_moved` writes the tasks moved out of a list with their numbers there,
+/// followed by a summary.
+pub(crate) fn report_moved(moved: &[(usize, Task)], verb: &str) {
+    let records: Vec<TaskRecord> = moved
+        .iter()
+        .map(|(number, task)| TaskRecord::new(*number, task))
+        .collect();
+
+    output::report(&json!({ "tasks": records }), || {
+        for (number, task) in moved {
+            println!("{number} - {}", task.to_string_short());
+        }
+
+        println!("{verb} {}.", count_tasks(moved.len()));
+    });
+}
+
+/// `batch_commands` makes a change to the selected tasks of the list in use,
+/// loading and saving the data file once while holding its lock.
+/// A removal is confirmed before taking the lock, so other commands are not kept waiting,
+/// and nothing is removed if the selected tasks changed in the meantime.
+///
+/// # Example
+///
+/// ```
+/// batch_commands(Batch::Done, select.selection(), false, &config::io::load());
+/// ```
+pub(crate) fn batch_commands(batch: Batch, selection: Selection, force: bool, config: &Config) {
+    let mut confirmed = None;
+
+    if batch == Batch::Remove && !force {
+        let tasks = tasks::io::load(&config.get_data_path());
+
+        match confirm(&tasks, batch, &selection, force) {
+            Ok(Some(selected)) => confirmed = Some(selected),
+            Ok(None) => return report(&Outcome::cancelled(batch)),
+            Err((code, message)) => output::fail(code, message),
+        }
+    }
+
+    let mut outcome = None;
+
+    tasks::io::update(config, |tasks| {
+        if let Some(confirmed) = &confirmed {
+            if selected(tasks, &selection).ok().as_ref() != Some(confirmed) {
+                output::fail(
+                    code::CONFLICT,
+                    "The tasks to remove were changed elsewhere, nothing was removed, run the command again.",
+                );
+            }
+        }
+
+        match change(tasks, batch, &selection) {
+            Ok(done) => outcome = Some(done),
+            Err((code, message)) => output::fail(code, message),
+        }
+    });
+
+    if let Some(outcome) = outcome {
+        report(&outcome);
+    }
+}
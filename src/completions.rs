@@ -0,0 +1,181 @@
+//! A module to handle the completion of the command line in the shells.
+//! The scripts from `todo completions <shell>` call `todo` back on every completion,
+//! so besides the commands and options it can complete the task numbers,
+//! with their names as descriptions, and the list names from the current data.
+
+use std::env;
+use std::io::{self, ErrorKind};
+
+use clap::{CommandFactory, FromArgMatches};
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{CompleteEnv, Shells};
+use todo::libs::config::{layers, Config};
+use todo::libs::output::code;
+use todo::libs::storage::store::{FileStore, MemoryStore, TaskStore};
+use todo::libs::tasks::archive::archive_path;
+use todo::libs::tasks::trash;
+use todo::libs::{config, output, project, tasks};
+
+use crate::{Cli, GlobalArgs};
+
+/// The variable the completion scripts set to ask for completions.
+const COMPLETE_ENV: &str = "COMPLETE";
+
+/// The shells there are completion scripts for.
+pub const SHELLS: [&str; 5] = ["bash", "elvish", "fish", "powershell", "zsh"];
+
+/// `complete_env` answers the completion scripts and exits, if they are the ones calling.
+/// It has to run before anything is written.
+pub fn complete_env() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_ENV)
+        .complete();
+}
+
+/// `completions_commands` writes the completion script of the given shell.
+pub fn completions_commands(shell: &str) {
+    let shells = Shells::builtins();
+
+    let Some(completer) = shells.completer(shell) else {
+        output::fail(code::INVALID_INPUT, format!("Unknown shell {shell:?}."));
+    };
+
+    let name = Cli::command().get_name().to_string();
+
+    match completer.write_registration(COMPLETE_ENV, &name, &name, &name, &mut io::stdout()) {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => {
+            output::fail(code::of_io(&err), format!("Completion script, {err}"));
+        }
+        _ => {}
+    }
+}
+
+/// `task_numbers` returns the numbers of the tasks of the current list, described by their names.
+pub fn task_numbers() -> Vec<CompletionCandidate> {
+    let Some(config) = current_config() else {
+        return vec![];
+    };
+
+    numbers_of(tasks::io::store(&config, config.get_data_path()).as_ref())
+}
+
+/// `archived_numbers` returns the numbers of the archived tasks of the current list,
+/// described by their names.
+pub fn archived_numbers() -> Vec<CompletionCandidate> {
+    let Some(config) = current_config() else {
+        return vec![];
+    };
+
+    numbers_of(&FileStore::new(archive_path(&config.get_data_path())))
+}
+
+/// `trashed_numbers` returns the numbers of the removed tasks of the current list,
+/// described by their names.
+pub fn trashed_numbers() -> Vec<CompletionCandidate> {
+    let Some(config) = current_config() else {
+        return vec![];
+    };
+
+    match trash::try_load(&config, &config.get_data_path()) {
+        Ok(trash) => numbers_of(&MemoryStore::with_tasks(trash.tasks())),
+        Err(_) => vec![],
+    }
+}
+
+/// `numbers_of` returns the numbers of the tasks of a store, described by their names.
+fn numbers_of(store: &dyn TaskStore) -> Vec<CompletionCandidate> {
+    match store.load() {
+        Ok(tasks) => tasks
+            .iter()
+            .enumerate()
+            .map(|(number, task)| {
+                CompletionCandidate::new(number.to_string()).help(Some(task.name.clone().into()))
+            })
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// `list_names` returns the names of the configured lists.
+pub fn list_names() -> Vec<CompletionCandidate> {
+    let globals = completed_globals();
+
+    if let Some(path) = globals
+        .as_ref()
+        .and_then(|globals| globals.config_path.clone())
+    {
+        config::io::use_config_path(path);
+    }
+
+    let options = globals.as_ref().map(overrides).unwrap_or_default();
+
+    config::io::load_lenient(&options)
+        .lists()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// `current_config` returns the configuration of the line being completed,
+/// with its `--list`, `--global`, `--config` and `-c` options like `load_config`.
+/// Problems are ignored, completion never fails: None is returned if the list does not exist.
+fn current_config() -> Option<Config> {
+    let globals = completed_globals();
+
+    if let Some(path) = globals
+        .as_ref()
+        .and_then(|globals| globals.config_path.clone())
+    {
+        config::io::use_config_path(path);
+    }
+
+    let options = globals.as_ref().map(overrides).unwrap_or_default();
+    let mut config = config::io::load_lenient(&options);
+
+    match globals {
+        Some(GlobalArgs {
+            list: Some(name), ..
+        }) => {
+            if !config.has_list(&name) {
+                return None;
+            }
+
+            config.select_list(&name);
+        }
+
+        Some(GlobalArgs { global: true, .. }) => {}
+
+        _ => {
+            if let Some(path) = env::current_dir()
+                .ok()
+                .and_then(|dir| project::discover(&dir))
+            {
+                config.use_project(path);
+            }
+        }
+    }
+
+    Some(config)
+}
+
+/// `overrides` returns the `-c` options of the line being completed, ignoring the invalid ones.
+fn overrides(globals: &GlobalArgs) -> Vec<(String, String)> {
+    globals
+        .config_options
+        .iter()
+        .filter_map(|pair| layers::parse_override(pair).ok())
+        .collect()
+}
+
+/// `completed_globals` returns the global options of the line being completed,
+/// the words the completion scripts pass after `--`.
+/// Words that do not parse yet, like the one being typed, are ignored.
+fn completed_globals() -> Option<GlobalArgs> {
+    let words = env::args_os().skip_while(|arg| arg != "--").skip(1);
+    let matches = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches_from(words)
+        .ok()?;
+
+    GlobalArgs::from_arg_matches(&matches).ok()
+}
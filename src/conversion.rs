@@ -1,18 +1,19 @@
+//! A module to handle the conversion of tasks
+//! from one format to another.
+//!
+//! # Purpose
+//!
+//! This macro are used for avoiding code duplication
+//! from the main.rs file.
+
 #![deny(missing_docs)]
 
 use clap::{Args, ValueEnum};
-use colored::Colorize;
 use serde_any::format::Format::{Json, Toml, Yaml};
+use serde_json::json;
 use std::path::PathBuf;
-use todo::libs::config;
-
-/// A module to handle the conversion of tasks
-/// from one format to another.
-///
-/// # Purpose
-///
-/// This macro are used for avoiding code duplication
-/// from the main.rs file.
+use todo::libs::config::Config;
+use todo::libs::output;
 
 /// A macro to load a set of tasks from a given path
 /// in the default config format (TOML) and export them
@@ -29,8 +30,10 @@ macro_rules! load_and_export {
         let tasks = ::todo::libs::tasks::io::load(&$config_path);
 
         if let Err(_) = ::todo::libs::storage::export_file(&tasks, $format, &$target_path) {
-            eprintln!("{} : Could not export to specified file.", "ERROR".red());
-            ::std::process::exit(exitcode::IOERR);
+            ::todo::libs::output::fail(
+                ::todo::libs::output::code::IO_ERROR,
+                "Could not export to specified file.",
+            );
         }
     };
 }
@@ -55,8 +58,10 @@ macro_rules! import_and_load {
                 ::todo::libs::tasks::io::save(&tasks, &$config_path);
             }
             Err(_) => {
-                eprintln!("{} : Could not import to specified file.", "ERROR".red());
-                ::std::process::exit(exitcode::IOERR);
+                ::todo::libs::output::fail(
+                    ::todo::libs::output::code::IO_ERROR,
+                    "Could not import to specified file.",
+                );
             }
         }
     };
@@ -88,11 +93,11 @@ macro_rules! convert {
 #[derive(Clone, ValueEnum)]
 pub enum Formats {
     /// Convert from TOML to JSON
-    JSON,
+    Json,
     /// Convert from TOML to YAML
-    YAML,
+    Yaml,
     /// Convert from TOML to TOML
-    TOML,
+    Toml,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -125,9 +130,9 @@ pub struct ConvertCommand {
 /// ```
 /// convert_commands(ConvertCommand {
 ///    action: ConvertAction::Import,
-///   format: Formats::JSON,
+///   format: Formats::Json,
 ///  path: PathBuf::from("tasks.json"),
-/// });
+/// }, config::io::load());
 /// ```
 pub(crate) fn convert_commands(
     ConvertCommand {
@@ -135,20 +140,21 @@ pub(crate) fn convert_commands(
         path,
         format,
     }: ConvertCommand,
+    config: Config,
 ) {
-    let config = config::io::load();
+    let target = json!({ "path": path });
 
     match format {
-        Formats::TOML => {
+        Formats::Toml => {
             convert!(action, config.get_data_path(), path, Toml)
         }
-        Formats::JSON => {
+        Formats::Json => {
             convert!(action, config.get_data_path(), path, Json)
         }
-        Formats::YAML => {
+        Formats::Yaml => {
             convert!(action, config.get_data_path(), path, Yaml)
         }
     };
 
-    config::io::save(&config)
+    output::report(&target, || {});
 }
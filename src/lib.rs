@@ -3,11 +3,17 @@
 /// It is divided into modules for better organization.
 /// The modules are:
 /// - config
+/// - output
+/// - project
+/// - render
 /// - storage
 /// - tasks
 ///
 pub mod libs {
     pub mod config;
+    pub mod output;
+    pub mod project;
+    pub mod render;
     pub mod storage;
     pub mod tasks;
 }
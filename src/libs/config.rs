@@ -1,19 +1,111 @@
-use std::path::PathBuf;
-use std::process;
+//! A module to handle the configuration of the application.
 
-use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::libs::config::check::Issue;
+use crate::libs::output::{self, code};
+use crate::libs::render::table::{self, Column};
+use crate::libs::render::template::Template;
+use crate::libs::render::theme::{self, Theme};
+use crate::libs::storage::migration;
+use crate::libs::tasks::order::Sort;
 use serde::{Deserialize, Serialize};
 
+pub mod check;
 pub mod io;
+pub mod layers;
+pub mod paths;
 
 const DATA_FILE_NAME: &str = "task.list";
+const DATA_FILE_EXTENSION: &str = "list";
+const DEFAULT_COMPACT_AFTER: usize = 100;
+const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// The name of the list stored in the data path, it always exists.
+pub const MAIN_LIST: &str = "main";
 
-/// A module to handle the configuration of the application.
+/// `StorageMode` is how changes to the tasks are written to the data file.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StorageMode {
+    /// Rewrite the whole data file on every change.
+    #[default]
+    Snapshot,
+    /// Append every change to an event log, compacted into the data file periodically.
+    EventLog,
+}
+
+/// `FromStr` is an implementation to parse the storage mode from the CLI.
+impl FromStr for StorageMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "snapshot" => Ok(StorageMode::Snapshot),
+            "event-log" => Ok(StorageMode::EventLog),
+            _ => Err(format!(
+                "unknown storage mode {mode:?}, expected snapshot or event-log"
+            )),
+        }
+    }
+}
 
 /// `Config` is a struct that contains the configuration of the application.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Config {
+    #[serde(default = "current_version")]
+    version: u32,
     data_path_file: Option<PathBuf>,
+    #[serde(default)]
+    storage: StorageMode,
+    #[serde(default = "default_compact_after")]
+    compact_after: usize,
+    #[serde(default)]
+    archive_after_days: Option<u64>,
+    #[serde(default = "default_trash_retention_days")]
+    trash_retention_days: u64,
+    #[serde(default)]
+    default_list: Option<String>,
+    #[serde(default)]
+    lists: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    table_columns: Option<String>,
+    #[serde(default)]
+    list_template: Option<String>,
+    #[serde(default)]
+    list_sort: Sort,
+    #[serde(default = "default_banner")]
+    banner: bool,
+    #[serde(default)]
+    theme: Theme,
+    #[serde(skip)]
+    selected_list: Option<String>,
+    #[serde(skip)]
+    project_data_path: Option<PathBuf>,
+    #[serde(skip)]
+    config_dir: Option<PathBuf>,
+}
+
+/// `default_compact_after` returns the default number of events kept in the log before compacting.
+fn default_compact_after() -> usize {
+    DEFAULT_COMPACT_AFTER
+}
+
+/// `default_trash_retention_days` returns the default number of days removed tasks are kept.
+fn default_trash_retention_days() -> u64 {
+    DEFAULT_TRASH_RETENTION_DAYS
+}
+
+/// `default_banner` returns if the `TODO` banner is shown by default.
+fn default_banner() -> bool {
+    true
+}
+
+/// `current_version` returns the version of the config file written by this build.
+fn current_version() -> u32 {
+    migration::CONFIG.version()
 }
 
 /// `Default` is an implementation of the default configuration.
@@ -21,7 +113,22 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Self {
         Config {
+            version: current_version(),
             data_path_file: None,
+            storage: StorageMode::default(),
+            compact_after: DEFAULT_COMPACT_AFTER,
+            archive_after_days: None,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            default_list: None,
+            lists: BTreeMap::new(),
+            table_columns: None,
+            list_template: None,
+            list_sort: Sort::default(),
+            banner: true,
+            theme: Theme::default(),
+            selected_list: None,
+            project_data_path: None,
+            config_dir: None,
         }
     }
 }
@@ -34,44 +141,389 @@ impl Config {
         self.data_path_file = new_path;
     }
 
-    /// `get_data_path` returns the data path.
-    /// If the path is None, it will be set to the default data directory.
+    /// `get_data_path` returns the data path of the list in use.
+    /// A selected list comes first, then the project list, then the default list.
+    /// For the main list, if the path is None, it will be set to the default data directory.
     pub fn get_data_path(&self) -> PathBuf {
-        match &self.data_path_file {
-            Some(path) => path.clone(),
-            None => Self::default_data_path(),
+        if let (None, Some(path)) = (&self.selected_list, &self.project_data_path) {
+            return path.clone();
+        }
+
+        self.get_list_path(self.active_list())
+    }
+
+    /// `use_project` uses the given project data path for this run,
+    /// unless a list is selected explicitly.
+    pub fn use_project(&mut self, path: PathBuf) {
+        self.project_data_path = Some(path);
+    }
+
+    /// `set_config_dir` sets the directory relative data paths are taken from,
+    /// the one of the config file.
+    pub fn set_config_dir(&mut self, dir: Option<PathBuf>) {
+        self.config_dir = dir;
+    }
+
+    /// `get_list_path` returns the data path of the given list, resolved with `resolve_path`.
+    /// If the list does not exist, it will exit with NOT_FOUND error code.
+    pub fn get_list_path(&self, name: &str) -> PathBuf {
+        if name == MAIN_LIST {
+            return match &self.data_path_file {
+                Some(path) => self.resolve_path(path),
+                None => Self::default_data_path(),
+            };
+        }
+
+        match self.lists.get(name) {
+            Some(path) => self.resolve_path(path),
+            None => output::fail(code::NOT_FOUND, format!("List {name:?} does not exist.")),
+        }
+    }
+
+    /// `resolve_path` expands `~` and the environment variables of a configured path,
+    /// and takes a relative path from the directory of the config file.
+    /// If the path cannot be expanded, it will exit with CONFIG error code.
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
+        match paths::resolve(path, self.config_dir.as_deref()) {
+            Ok(path) => path,
+            Err(message) => config_error(&format!("{message}, in {path:?}.")),
+        }
+    }
+
+    /// `lists` returns the names of all the lists, starting with the main list.
+    pub fn lists(&self) -> Vec<String> {
+        let mut names = vec![MAIN_LIST.to_string()];
+        names.extend(self.lists.keys().cloned());
+        names
+    }
+
+    /// `active_list` returns the name of the list in use.
+    /// That is the selected list, or the default list, or the main list.
+    pub fn active_list(&self) -> &str {
+        self.selected_list
+            .as_deref()
+            .or(self.default_list.as_deref())
+            .unwrap_or(MAIN_LIST)
+    }
+
+    /// `get_default_list` returns the name of the list used when none is selected.
+    pub fn get_default_list(&self) -> &str {
+        self.default_list.as_deref().unwrap_or(MAIN_LIST)
+    }
+
+    /// `select_list` selects the list to use for this run, without changing the default.
+    /// If the list does not exist, it will exit with NOT_FOUND error code.
+    pub fn select_list(&mut self, name: &str) {
+        self.ensure_list(name);
+        self.selected_list = Some(name.to_string());
+    }
+
+    /// `create_list` creates a new list stored in the given path.
+    /// If the path is None, it is stored next to the main list as `<name>.list`.
+    /// If the list already exists, it will exit with CONFLICT error code.
+    pub fn create_list(&mut self, name: &str, path: Option<PathBuf>) {
+        Self::ensure_valid_name(name);
+
+        if self.has_list(name) {
+            output::fail(code::CONFLICT, format!("List {name:?} already exists."));
+        }
+
+        let path = path.unwrap_or_else(|| {
+            self.data_path_file
+                .clone()
+                .unwrap_or_else(Self::default_data_path)
+                .with_file_name(name)
+                .with_extension(DATA_FILE_EXTENSION)
+        });
+
+        self.lists.insert(name.to_string(), path);
+    }
+
+    /// `rename_list` renames a list, its data file is kept in place.
+    /// If the list does not exist or the new name is taken,
+    /// it will exit with NOT_FOUND or CONFLICT error code.
+    pub fn rename_list(&mut self, name: &str, new_name: &str) {
+        Self::ensure_not_main(name);
+        Self::ensure_valid_name(new_name);
+        self.ensure_list(name);
+
+        if self.has_list(new_name) {
+            output::fail(code::CONFLICT, format!("List {new_name:?} already exists."));
+        }
+
+        if let Some(path) = self.lists.remove(name) {
+            self.lists.insert(new_name.to_string(), path);
+        }
+
+        if self.default_list.as_deref() == Some(name) {
+            self.default_list = Some(new_name.to_string());
+        }
+    }
+
+    /// `delete_list` deletes a list and returns the resolved path of its data file.
+    /// If it was the default list, the main list becomes the default.
+    /// If the list does not exist, it will exit with NOT_FOUND error code.
+    pub fn delete_list(&mut self, name: &str) -> PathBuf {
+        Self::ensure_not_main(name);
+        self.ensure_list(name);
+
+        if self.default_list.as_deref() == Some(name) {
+            self.default_list = None;
+        }
+
+        let path = self.get_list_path(name);
+        self.lists.remove(name);
+        path
+    }
+
+    /// `set_default_list` sets the list used when none is selected.
+    /// If the list does not exist, it will exit with NOT_FOUND error code.
+    pub fn set_default_list(&mut self, name: &str) {
+        self.ensure_list(name);
+
+        self.default_list = match name {
+            MAIN_LIST => None,
+            _ => Some(name.to_string()),
+        };
+    }
+
+    /// `has_list` checks if a list with the given name exists.
+    pub fn has_list(&self, name: &str) -> bool {
+        name == MAIN_LIST || self.lists.contains_key(name)
+    }
+
+    /// `ensure_list` exits with NOT_FOUND error code if the list does not exist.
+    fn ensure_list(&self, name: &str) {
+        if !self.has_list(name) {
+            output::fail(code::NOT_FOUND, format!("List {name:?} does not exist."));
+        }
+    }
+
+    /// `ensure_not_main` exits with INVALID_INPUT error code if the list is the main list.
+    fn ensure_not_main(name: &str) {
+        if name == MAIN_LIST {
+            output::fail(
+                code::INVALID_INPUT,
+                format!("The {MAIN_LIST:?} list cannot be renamed or deleted."),
+            );
+        }
+    }
+
+    /// `ensure_valid_name` exits with INVALID_INPUT error code if the name cannot be used for a list.
+    /// Names are made of letters, numbers, `-` and `_`.
+    fn ensure_valid_name(name: &str) {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '-' || char == '_');
+
+        if !valid {
+            output::fail(
+                code::INVALID_INPUT,
+                format!("Invalid list name {name:?}, use only letters, numbers, '-' and '_'."),
+            );
+        }
+    }
+
+    /// `set_storage` sets how the tasks are written,
+    /// and optionally after how many events the log is compacted.
+    pub fn set_storage(&mut self, mode: StorageMode, compact_after: Option<usize>) {
+        self.storage = mode;
+
+        if let Some(compact_after) = compact_after {
+            self.compact_after = compact_after;
         }
     }
 
+    /// `get_storage` returns how the tasks are written.
+    pub fn get_storage(&self) -> StorageMode {
+        self.storage
+    }
+
+    /// `get_compact_after` returns after how many events the log is compacted.
+    pub fn get_compact_after(&self) -> usize {
+        self.compact_after
+    }
+
+    /// `get_archive_after_days` returns after how many days completed tasks are archived,
+    /// if they are archived automatically.
+    pub fn get_archive_after_days(&self) -> Option<u64> {
+        self.archive_after_days
+    }
+
+    /// `get_trash_retention_days` returns after how many days removed tasks leave the trash.
+    pub fn get_trash_retention_days(&self) -> u64 {
+        self.trash_retention_days
+    }
+
+    /// `get_table_columns` returns the columns of the table view, in order.
+    /// If none are configured, every column is shown.
+    pub fn get_table_columns(&self) -> Vec<Column> {
+        self.table_columns
+            .as_deref()
+            .and_then(|columns| table::parse_columns(columns).ok())
+            .unwrap_or_else(|| table::DEFAULT_COLUMNS.to_vec())
+    }
+
+    /// `get_list_template` returns the template of every line of `todo list`, if one is configured.
+    pub fn get_list_template(&self) -> Option<Template> {
+        self.list_template
+            .as_deref()
+            .and_then(|template| Template::parse(template).ok())
+    }
+
+    /// `get_list_sort` returns the order `todo list` shows the tasks in.
+    pub fn get_list_sort(&self) -> Sort {
+        self.list_sort
+    }
+
+    /// `show_banner` checks if the `TODO` banner is shown before the output.
+    pub fn show_banner(&self) -> bool {
+        self.banner
+    }
+
+    /// `get_theme` returns the colors of the output.
+    pub fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
     /// `validate` validates the configuration.
-    /// If the data path is not absolute, it will exit with CONFIG error code.
+    /// If there is any error, all the issues are printed and it will exit with CONFIG error code.
+    /// Refer to `issues` for the rules.
     pub fn validate(self) -> Self {
+        let issues = self.issues();
+
+        if issues.iter().any(Issue::is_error) {
+            check::fail(&issues);
+        }
+
+        self
+    }
+
+    /// `issues` returns every problem of the configuration.
+    /// The data path and the paths of the named lists must resolve to files
+    /// in an existing directory, the default list must exist,
+    /// the table columns, the list template and the theme must be valid
+    /// and the log compaction threshold, the days before archiving
+    /// and the days in the trash must be greater than 0.
+    pub fn issues(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+
         if let Some(path) = &self.data_path_file {
-            if !path.is_absolute() {
-                eprintln!("{} : Data path cannot be relative.", "CONFIG - ERROR".red());
-                process::exit(exitcode::CONFIG);
+            issues.extend(self.path_issues("Data path", "data_path_file", path));
+        }
+
+        for (name, path) in &self.lists {
+            issues.extend(self.path_issues(
+                &format!("Path of list {name:?}"),
+                &format!("lists.{name}"),
+                path,
+            ));
+        }
+
+        if let Some(name) = &self.default_list {
+            if !self.lists.contains_key(name) {
+                issues.push(
+                    Issue::error(format!("Default list {name:?} does not exist.")).suggest(
+                        format!("create it with `todo lists create {name}` or run `todo config unset default_list`"),
+                    ),
+                );
             }
+        }
+
+        if let Some(Err(message)) = self.table_columns.as_deref().map(table::parse_columns) {
+            issues.push(
+                Issue::error(format!("Invalid table columns, {message}."))
+                    .suggest("run `todo config set table_columns id,status,name,description`"),
+            );
+        }
 
-            if path.is_dir() {
-                eprintln!(
-                    "{} : Data path cannot be a directory.",
-                    "CONFIG - ERROR".red()
+        if let Some(Err(message)) = self.list_template.as_deref().map(Template::parse) {
+            issues.push(
+                Issue::error(format!("Invalid list template, {message}."))
+                    .suggest("run `todo config unset list_template` to use the default layout"),
+            );
+        }
+
+        for (role, style) in self.theme.roles() {
+            if let Err(message) = theme::parse_style(style) {
+                issues.push(
+                    Issue::error(format!("Invalid style for theme.{role}, {message}.")).suggest(
+                        format!("run `todo config unset theme.{role}` to use the default"),
+                    ),
                 );
-                process::exit(exitcode::CONFIG);
             }
+        }
+
+        if self.compact_after == 0 {
+            issues.push(
+                Issue::error("Compact after must be greater than 0.").suggest(format!(
+                    "run `todo config set compact_after {DEFAULT_COMPACT_AFTER}`"
+                )),
+            );
+        }
+
+        if self.trash_retention_days == 0 {
+            issues.push(
+                Issue::error("Trash retention days must be greater than 0.").suggest(format!(
+                    "run `todo config set trash_retention_days {DEFAULT_TRASH_RETENTION_DAYS}`"
+                )),
+            );
+        }
+
+        if self.archive_after_days == Some(0) {
+            issues.push(
+                Issue::error("Archive after days must be greater than 0.")
+                    .suggest("run `todo config unset archive_after_days` to keep completed tasks"),
+            );
+        }
 
-            if let Some(path) = path.parent() {
-                if !path.is_dir() || !path.exists() {
-                    eprintln!(
-                        "{} : Data path has no valid parent directory.",
-                        "CONFIG - ERROR".red()
-                    );
-                    process::exit(exitcode::CONFIG);
-                }
+        issues
+    }
+
+    /// `path_issues` returns the problems of a data path, named as `label` in the messages
+    /// and set with `key`.
+    fn path_issues(&self, label: &str, key: &str, path: &Path) -> Vec<Issue> {
+        let mut issues = vec![];
+
+        let path = match paths::resolve(path, self.config_dir.as_deref()) {
+            Ok(path) => path,
+            Err(message) => {
+                issues.push(
+                    Issue::error(format!("{label} cannot be resolved, {message}.")).suggest(
+                        format!("set the variable or run `todo config set {key} <path>`"),
+                    ),
+                );
+                return issues;
             }
+        };
+
+        if !path.is_absolute() {
+            issues.push(
+                Issue::error(format!("{label} cannot be relative without a config file."))
+                    .suggest(format!("run `todo config set {key} <absolute path>`")),
+            );
         }
 
-        self
+        if path.is_dir() {
+            issues.push(
+                Issue::error(format!("{label} cannot be a directory.")).suggest(format!(
+                    "point it to a file inside, like {:?}",
+                    path.join(DATA_FILE_NAME)
+                )),
+            );
+        }
+
+        if let Some(parent) = path.parent() {
+            if path.is_absolute() && (!parent.is_dir() || !parent.exists()) {
+                issues.push(
+                    Issue::error(format!("{label} has no valid parent directory."))
+                        .suggest(format!("create the directory {parent:?} first")),
+                );
+            }
+        }
+
+        issues
     }
 
     /// `default_data_path` returns the default data path.
@@ -84,9 +536,19 @@ impl Config {
             }
 
             None => {
-                eprintln!("{} : Data Directory not found.", "ERROR".red());
-                process::exit(exitcode::IOERR);
+                output::fail(code::IO_ERROR, "Data Directory not found.");
             }
         }
     }
 }
+
+/// `config_error` prints a configuration error and exits with CONFIG error code.
+fn config_error(message: &str) -> ! {
+    output::fail_with("CONFIG - ERROR", code::CONFIG_ERROR, message)
+}
+
+/// `input_error` prints an error about a key or a value given on the command line
+/// and exits with INVALID_INPUT error code.
+fn input_error(message: &str) -> ! {
+    output::fail_with("CONFIG - ERROR", code::INVALID_INPUT, message)
+}
@@ -0,0 +1,192 @@
+//! A module to report the problems found in the configuration.
+//! Problems are collected as issues instead of stopping at the first one,
+//! so they can all be reported at once with a suggestion to fix them.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::process;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::libs::output::{self, code};
+use crate::libs::render::theme::{paint, theme};
+
+/// `Severity` is how serious an issue is.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The configuration cannot be used.
+    Error,
+    /// The configuration can be used, but something is likely a mistake.
+    Warning,
+}
+
+/// `Issue` is a problem found in the configuration.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+/// `Issue` is an implementation of the issue.
+impl Issue {
+    /// `error` creates an issue that makes the configuration unusable.
+    pub fn error(message: impl Into<String>) -> Issue {
+        Issue {
+            severity: Severity::Error,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    /// `warning` creates an issue that is likely a mistake.
+    pub fn warning(message: impl Into<String>) -> Issue {
+        Issue {
+            severity: Severity::Warning,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    /// `suggest` adds a suggestion on how to fix the issue.
+    pub fn suggest(mut self, suggestion: impl Into<String>) -> Issue {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// `is_error` checks if the issue makes the configuration unusable.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// `Display` is an implementation of the display for `Issue`.
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => paint("CONFIG - ERROR", &theme().error),
+            Severity::Warning => paint("CONFIG - WARNING", &theme().warning),
+        };
+
+        write!(f, "{} : {}", label, self.message)?;
+
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n    {} {}", paint("hint:", &theme().hint), suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `report` prints all the issues to the standard error.
+pub fn report(issues: &[Issue]) {
+    for issue in issues {
+        eprintln!("{issue}");
+    }
+}
+
+/// `fail` reports all the issues and exits with CONFIG error code.
+/// In the structured output formats, they are written as the issues of an `error`.
+pub fn fail(issues: &[Issue]) -> ! {
+    if output::is_human() {
+        report(issues);
+    } else {
+        output::emit(&json!({
+            "error": {
+                "code": code::CONFIG_ERROR,
+                "kind": code::kind(code::CONFIG_ERROR),
+                "message": "Invalid configuration.",
+                "issues": issues,
+            }
+        }));
+    }
+
+    process::exit(code::CONFIG_ERROR);
+}
+
+/// `closest` returns the candidate closest to the given word, if any is close enough.
+/// Used to suggest the key the user likely meant.
+pub fn closest<'a>(word: &str, candidates: &'a [String]) -> Option<&'a String> {
+    candidates
+        .iter()
+        .map(|candidate| (distance(word, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(3) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// `distance` returns the edit distance between two words.
+fn distance(first: &str, second: &str) -> usize {
+    let second: Vec<char> = second.chars().collect();
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+
+    for (i, a) in first.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[second.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `keys` returns some config keys to suggest.
+    fn keys() -> Vec<String> {
+        ["banner", "storage", "compact_after", "trash_retention_days"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("banner", "banner"), 0);
+        assert_eq!(distance("baner", "banner"), 1);
+        assert_eq!(distance("bannre", "banner"), 2);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("é", "e"), 1);
+    }
+
+    #[test]
+    fn exact_matches_are_suggested() {
+        assert_eq!(
+            closest("storage", &keys()).map(String::as_str),
+            Some("storage")
+        );
+    }
+
+    #[test]
+    fn near_misses_are_suggested() {
+        assert_eq!(
+            closest("baner", &keys()).map(String::as_str),
+            Some("banner")
+        );
+        assert_eq!(
+            closest("compact-after", &keys()).map(String::as_str),
+            Some("compact_after")
+        );
+        assert_eq!(closest("bnnr", &keys()).map(String::as_str), Some("banner"));
+        assert_eq!(
+            closest("trash_retention_day", &keys()).map(String::as_str),
+            Some("trash_retention_days")
+        );
+    }
+
+    #[test]
+    fn distant_words_are_not_suggested() {
+        assert_eq!(closest("colour", &keys()), None);
+        assert_eq!(closest("bnr", &keys()), None);
+        assert_eq!(closest("compact", &keys()), None);
+        assert_eq!(closest("banner", &[]), None);
+    }
+}
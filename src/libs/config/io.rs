@@ -1,17 +1,50 @@
-use std::path::PathBuf;
-use std::{io, process};
+//! A module to handle the configuration of the application.
 
-use crate::libs::config::Config;
-use crate::libs::storage::{export_file, import_file_or_create};
-use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::{env, io};
+
+use crate::libs::config::check::{self, Issue};
+use crate::libs::config::layers::{self, Layered, Sources};
+use crate::libs::config::{config_error, input_error, Config};
+use crate::libs::output::{self, code};
+use crate::libs::project;
+use crate::libs::storage::export_file;
+use crate::libs::storage::migration::{self, import_file_versioned};
+use crate::libs::storage::TablesLast;
 use serde_any::Format;
+use serde_json::{Map, Value};
 
 const CONFIG_FILE_NAME: &str = "todo.config";
 
-/// A module to handle the configuration of the application.
+/// The environment variable that overrides the path of the user config file.
+pub const CONFIG_PATH_ENV: &str = "TODO_CONFIG";
+
+/// The path of the user config file given on the command line, if any.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// `use_config_path` makes the given file the user config file for this run,
+/// instead of `TODO_CONFIG` or the default path.
+pub fn use_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// `config_path` returns the path of the user config file.
+/// In order, it is the one given to `use_config_path`,
+/// the one in the `TODO_CONFIG` environment variable, or the default one.
+pub fn config_path() -> PathBuf {
+    if let Some(path) = CONFIG_PATH.get() {
+        return path.clone();
+    }
+
+    match env::var_os(CONFIG_PATH_ENV) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => default_config_path(),
+    }
+}
 
 /// `default_config_path` returns the default config path.
-pub(crate) fn default_config_path() -> PathBuf {
+fn default_config_path() -> PathBuf {
     match dirs::config_dir() {
         Some(mut path) => {
             path.push(CONFIG_FILE_NAME);
@@ -19,29 +52,175 @@ pub(crate) fn default_config_path() -> PathBuf {
         }
 
         None => {
-            eprintln!("{} : Config directory not found.", "ERROR".red());
-            process::exit(exitcode::IOERR);
+            output::fail(code::IO_ERROR, "Config directory not found.");
         }
     }
 }
 
-/// `save` saves the configuration to the default config path.
+/// `sources` returns where every layer of the configuration is read from,
+/// with the given `key=value` pairs from the command line as the top layer.
+pub fn sources(cli: &[(String, String)]) -> Sources {
+    Sources {
+        system: layers::system_config_path(),
+        user: std::path::absolute(config_path()).unwrap_or_else(|_| config_path()),
+        project: env::current_dir()
+            .ok()
+            .and_then(|dir| project::discover_config(&dir)),
+        env: layers::env_values(),
+        cli: cli.to_vec(),
+    }
+}
+
+/// `save` saves the configuration to the user config file.
+///
+/// Only the values that differ from the defaults, or that the file already had,
+/// are written, so the file does not hide the values of the system config.
 pub fn save(config: &Config) {
-    if let Err(err) = export_file(config, Format::Toml, &default_config_path()) {
-        eprintln!("{} : Config file, {}", "ERROR".red(), err);
-        process::exit(exitcode::IOERR);
+    let existing = read_user();
+    let defaults = layers::to_map(&Config::default());
+
+    let values: Map<_, _> = layers::to_map(config)
+        .into_iter()
+        .filter(|(key, value)| existing.contains_key(key) || defaults.get(key) != Some(value))
+        .collect();
+
+    write_user(values);
+}
+
+/// `get_value` returns the effective value of a dotted key,
+/// with the given `key=value` pairs from the command line as the top layer.
+/// If the key is unknown, it will exit with INVALID_INPUT error code.
+pub fn get_value(cli: &[(String, String)], key: &str) -> Option<Value> {
+    if !layers::is_known_key(key) {
+        input_error(&layers::unknown_key(key));
+    }
+
+    load_layered(cli).get(key).cloned()
+}
+
+/// `set_value` sets a dotted key of the user config file to the given raw value.
+/// The value is parsed with the type the `Config` struct has for the key,
+/// and a relative data path is taken from the current directory.
+/// If the key is unknown or the value is invalid, it will exit with INVALID_INPUT error code.
+/// If the resulting configuration is not valid, it will exit with CONFIG error code.
+pub fn set_value(key: &str, raw: &str) {
+    let mut values = read_user();
+
+    if let Err(message) = layers::set(&mut values, key, raw) {
+        input_error(&message);
     }
+
+    layers::anchor_from_current_dir(&mut values, key);
+    parse(&values).validate();
+    write_user(values);
 }
 
-/// `load` loads the configuration from the default config path.
+/// `unset_value` removes a dotted key from the user config file,
+/// so the value of a lower layer, or the default, is used again.
+/// Returns if the key was set. If the key is unknown, it will exit with INVALID_INPUT error code.
+pub fn unset_value(key: &str) -> bool {
+    if !layers::is_known_key(key) {
+        input_error(&layers::unknown_key(key));
+    }
+
+    let mut values = read_user();
+    let removed = layers::remove(&mut values, key);
+
+    if removed {
+        parse(&values).validate();
+        write_user(values);
+    }
+
+    removed
+}
+
+/// `read_user` reads the raw values of the user config file, none if it does not exist.
+fn read_user() -> Map<String, Value> {
+    let path = config_path();
+
+    if path.exists() {
+        layers::read(&path)
+    } else {
+        Map::new()
+    }
+}
+
+/// `write_user` writes the raw values to the user config file, stamped with the current version.
+fn write_user(mut values: Map<String, Value>) {
+    values.insert(
+        String::from("version"),
+        Value::from(migration::CONFIG.version()),
+    );
+
+    if let Err(err) = export_file(&TablesLast(&values), Format::Toml, &config_path()) {
+        output::fail(code::of_io(&err), format!("Config file, {err}"));
+    }
+}
+
+/// `parse` converts raw values into a configuration.
+/// If they do not match the `Config` struct, it will exit with CONFIG error code.
+fn parse(values: &Map<String, Value>) -> Config {
+    match serde_json::from_value::<Config>(Value::Object(values.clone())) {
+        Ok(mut config) => {
+            config.set_config_dir(config_dir());
+            config
+        }
+        Err(err) => config_error(&err.to_string()),
+    }
+}
+
+/// `config_dir` returns the directory of the user config file,
+/// the one relative data paths are taken from.
+fn config_dir() -> Option<PathBuf> {
+    std::path::absolute(config_path())
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+}
+
+/// `load` loads the effective configuration out of all the layers.
 pub fn load() -> Config {
-    let result: io::Result<Config> = import_file_or_create(&default_config_path(), Format::Toml);
+    load_layered(&[]).config
+}
+
+/// `load_layered` loads the effective configuration out of all the layers,
+/// with the given `key=value` pairs from the command line as the top layer,
+/// keeping where every value came from.
+/// If there is any error, all the issues are printed and it will exit with CONFIG error code.
+pub fn load_layered(cli: &[(String, String)]) -> Layered {
+    let layered = layers::load(&sources(cli));
+    let issues = layered.issues();
+
+    if issues.iter().any(Issue::is_error) {
+        check::fail(&issues);
+    }
+
+    layered
+}
+
+/// `load_lenient` loads the effective configuration out of all the layers like `load_layered`,
+/// but keeps going when there are errors, for settings that are needed before any command runs.
+pub fn load_lenient(cli: &[(String, String)]) -> Config {
+    layers::load(&sources(cli)).config
+}
+
+/// `check` returns every problem of the configuration out of all the layers,
+/// with the given `key=value` pairs from the command line as the top layer.
+pub fn check(cli: &[(String, String)]) -> Vec<Issue> {
+    layers::load(&sources(cli)).issues()
+}
+
+/// `load_user` loads the user config file alone, to be modified and saved back.
+pub fn load_user() -> Config {
+    let result: io::Result<Config> =
+        import_file_versioned(&config_path(), Format::Toml, &migration::CONFIG);
 
     match result {
-        Ok(data) => data.validate(),
+        Ok(mut data) => {
+            data.set_config_dir(config_dir());
+            data.validate()
+        }
         Err(err) => {
-            eprintln!("{} : Config file, {}", "ERROR".red(), err);
-            process::exit(exitcode::IOERR);
+            output::fail(code::of_io(&err), format!("Config file, {err}"));
         }
     }
 }
@@ -0,0 +1,681 @@
+//! A module to build the configuration out of layers.
+//! From the lowest to the highest priority, the layers are:
+//! the built-in defaults, the system config, the user config,
+//! the project config, the `TODO_*` environment variables and the CLI flags.
+//! Every effective value remembers the layer it came from.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use serde_any::Format;
+use serde_json::{Map, Value};
+
+use crate::libs::config::check::{self, Issue};
+use crate::libs::config::{config_error, paths, Config};
+use crate::libs::storage::migration::{self, read_file_versioned_raw};
+
+/// The prefix of the environment variables read as configuration.
+pub const ENV_PREFIX: &str = "TODO_";
+
+/// `Layer` is a source of configuration values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    /// The built-in defaults.
+    Default,
+    /// The config file shared by every user of the system.
+    System,
+    /// The config file of the user.
+    User,
+    /// The config file of the project in the current directory.
+    Project,
+    /// The `TODO_*` environment variables.
+    Environment,
+    /// The flags given on the command line.
+    Cli,
+}
+
+/// `Display` is an implementation of the display for `Layer`.
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Layer::Default => "default",
+            Layer::System => "system",
+            Layer::User => "user",
+            Layer::Project => "project",
+            Layer::Environment => "env",
+            Layer::Cli => "cli",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// `Origin` is where an effective value came from,
+/// the layer and the file or variable within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub layer: Layer,
+    pub source: Option<String>,
+}
+
+/// `Display` is an implementation of the display for `Origin`.
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{} ({})", self.layer, source),
+            None => write!(f, "{}", self.layer),
+        }
+    }
+}
+
+/// `Layered` is the effective configuration together with the origin of every value.
+#[derive(Debug, Clone)]
+pub struct Layered {
+    pub config: Config,
+    values: Map<String, Value>,
+    origins: BTreeMap<String, Origin>,
+    issues: Vec<Issue>,
+}
+
+/// `Layered` is an implementation of the layered configuration.
+impl Layered {
+    /// `entries` returns every effective value as a dotted key, the value and its origin.
+    pub fn entries(&self) -> Vec<(String, Value, Origin)> {
+        let default = Origin {
+            layer: Layer::Default,
+            source: None,
+        };
+
+        flatten(&self.values)
+            .into_iter()
+            .filter(|(key, _)| key != "version")
+            .map(|(key, value)| {
+                let origin = self.origins.get(&key).unwrap_or(&default).clone();
+                (key, value, origin)
+            })
+            .collect()
+    }
+
+    /// `get` returns the effective value of a dotted key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        get(&self.values, key)
+    }
+
+    /// `issues` returns the problems found while building the configuration,
+    /// followed by the problems of the effective configuration itself.
+    pub fn issues(&self) -> Vec<Issue> {
+        let mut issues = self.issues.clone();
+        issues.extend(self.config.issues());
+        issues
+    }
+}
+
+/// `Sources` are the places the layers are read from.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    pub system: Option<PathBuf>,
+    pub user: PathBuf,
+    pub project: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+    pub cli: Vec<(String, String)>,
+}
+
+/// `system_config_path` returns the path of the system config, if the platform has one.
+pub fn system_config_path() -> Option<PathBuf> {
+    if cfg!(unix) {
+        Some(PathBuf::from("/etc/todo/todo.config"))
+    } else {
+        None
+    }
+}
+
+/// `env_values` returns the `TODO_*` environment variables that match a config key,
+/// as the key and the raw value. `TODO_COMPACT_AFTER` sets `compact_after`.
+pub fn env_values() -> Vec<(String, String)> {
+    let keys = top_level_keys();
+
+    env::vars()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+            keys.contains(&key).then_some((key, value))
+        })
+        .collect()
+}
+
+/// `parse_override` parses a `key=value` pair given with `-c` on the command line.
+/// A pair without `=` is an error, to be reported as a configuration error.
+pub fn parse_override(pair: &str) -> Result<(String, String), String> {
+    match pair.split_once('=') {
+        Some((key, value)) => Ok((key.trim().to_string(), value.to_string())),
+        None => Err(format!("Invalid -c {pair:?}, expected KEY=VALUE.")),
+    }
+}
+
+/// `load` builds the effective configuration out of the given sources.
+///
+/// Relative data paths are taken from the directory of the file that sets them,
+/// the current directory for the environment and the command line.
+/// Those of the user config are kept as they are and resolved by the configuration.
+///
+/// Problems do not stop the loading, they are collected as issues instead:
+/// unknown keys in files are warnings and are ignored,
+/// invalid values are errors and the value of the lower layer is kept.
+pub fn load(sources: &Sources) -> Layered {
+    let mut values = to_map(&Config::default());
+    let mut origins = BTreeMap::new();
+    let mut issues = vec![];
+
+    let files = [
+        (Layer::System, sources.system.as_ref()),
+        (Layer::User, Some(&sources.user)),
+        (Layer::Project, sources.project.as_ref()),
+    ];
+
+    for (layer, path) in files {
+        let Some(path) = path.filter(|path| path.exists()) else {
+            continue;
+        };
+
+        let origin = Origin {
+            layer,
+            source: Some(path.display().to_string()),
+        };
+
+        let mut data = match try_read(path) {
+            Ok(data) => data,
+            Err(message) => {
+                issues.push(Issue::error(message).suggest("fix the syntax of the file"));
+                continue;
+            }
+        };
+
+        data.remove("version");
+
+        if let (Layer::System | Layer::Project, Some(dir)) = (layer, path.parent()) {
+            anchor(&mut data, dir);
+        }
+
+        merge(&mut values, data, &origin, &mut origins, &mut issues);
+    }
+
+    let overrides = [
+        (Layer::Environment, &sources.env),
+        (Layer::Cli, &sources.cli),
+    ];
+
+    for (layer, pairs) in overrides {
+        for (key, raw) in pairs {
+            let source = match layer {
+                Layer::Environment => format!("{ENV_PREFIX}{}", key.to_uppercase()),
+                _ => format!("-c {key}"),
+            };
+
+            let origin = Origin {
+                layer,
+                source: Some(source),
+            };
+
+            match set(&mut values, key, raw) {
+                Ok(()) => {
+                    anchor_from_current_dir(&mut values, key);
+                    origins.insert(key.clone(), origin);
+                }
+                Err(message) => issues.push(
+                    Issue::error(format!("{message} (from {origin})"))
+                        .suggest(suggestion(key).unwrap_or_else(|| format!("check {origin}"))),
+                ),
+            }
+        }
+    }
+
+    let mut config: Config = match serde_json::from_value(Value::Object(values.clone())) {
+        Ok(config) => config,
+        Err(err) => {
+            issues.push(Issue::error(err.to_string()));
+            Config::default()
+        }
+    };
+
+    config.set_config_dir(sources.user.parent().map(Path::to_path_buf));
+
+    Layered {
+        config,
+        values,
+        origins,
+        issues,
+    }
+}
+
+/// `read` reads the raw values of a config file, upgrading them in memory if needed.
+/// If the file cannot be read, the program will exit.
+pub(crate) fn read(path: &Path) -> Map<String, Value> {
+    match try_read(path) {
+        Ok(data) => data,
+        Err(message) => config_error(&message),
+    }
+}
+
+/// `try_read` works like `read`, but returns a message if the file cannot be read.
+fn try_read(path: &Path) -> Result<Map<String, Value>, String> {
+    read_file_versioned_raw(&path.to_path_buf(), Format::Toml, &migration::CONFIG)
+        .map_err(|err| format!("{}, {}", path.display(), err))
+}
+
+/// `to_map` returns the raw values of a configuration.
+pub(crate) fn to_map(config: &Config) -> Map<String, Value> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(values)) => values,
+        _ => Map::new(),
+    }
+}
+
+/// `top_level_keys` returns the keys of the configuration that hold a single value.
+fn top_level_keys() -> Vec<String> {
+    to_map(&Config::default())
+        .into_iter()
+        .filter(|(key, value)| key != "version" && !value.is_object())
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// `merge` merges the values of a file layer into the effective values, key by key.
+/// Unknown keys and invalid values are reported as issues and left out.
+fn merge(
+    values: &mut Map<String, Value>,
+    layer: Map<String, Value>,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+    issues: &mut Vec<Issue>,
+) {
+    for (key, value) in flatten(&layer) {
+        if !is_known_key(&key) {
+            let mut issue =
+                Issue::warning(format!("Unknown key {key:?} in {origin}, it is ignored."));
+
+            if let Some(suggestion) = suggestion(&key) {
+                issue = issue.suggest(suggestion);
+            }
+
+            issues.push(issue);
+            continue;
+        }
+
+        let mut attempt = values.clone();
+        insert(&mut attempt, &key, value);
+
+        match serde_json::from_value::<Config>(Value::Object(attempt.clone())) {
+            Ok(_) => {
+                *values = attempt;
+                origins.insert(key, origin.clone());
+            }
+            Err(err) => issues.push(
+                Issue::error(format!("Invalid value for {key} in {origin}, {err}."))
+                    .suggest(format!("run `todo config unset {key}` to use the default")),
+            ),
+        }
+    }
+}
+
+/// `suggestion` returns a hint for an unknown key, naming the known key it is closest to.
+fn suggestion(key: &str) -> Option<String> {
+    if is_known_key(key) {
+        return None;
+    }
+
+    check::closest(key, &known_keys()).map(|known| format!("did you mean {known:?}?"))
+}
+
+/// `known_keys` returns the dotted keys of the configuration.
+fn known_keys() -> Vec<String> {
+    flatten(&to_map(&Config::default()))
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| key != "version")
+        .collect()
+}
+
+/// `is_path_key` checks if a dotted key holds a data path.
+fn is_path_key(key: &str) -> bool {
+    key == "data_path_file" || key.starts_with("lists.")
+}
+
+/// `anchor` takes the relative data paths of a layer from the given directory.
+fn anchor(values: &mut Map<String, Value>, dir: &Path) {
+    for (key, _) in flatten(values) {
+        if is_path_key(&key) {
+            anchor_key(values, &key, dir);
+        }
+    }
+}
+
+/// `anchor_from_current_dir` takes the data path under a dotted key from the current directory,
+/// if the key holds one and it is relative, as it was typed on the command line.
+pub(crate) fn anchor_from_current_dir(values: &mut Map<String, Value>, key: &str) {
+    if let (true, Ok(dir)) = (is_path_key(key), env::current_dir()) {
+        anchor_key(values, key, &dir);
+    }
+}
+
+/// `anchor_key` takes the data path under a dotted key from the given directory, if relative.
+fn anchor_key(values: &mut Map<String, Value>, key: &str, dir: &Path) {
+    if let Some(Value::String(path)) = get(values, key) {
+        if !paths::is_anchored(Path::new(path)) {
+            let path = dir.join(path).display().to_string();
+            insert(values, key, Value::from(path));
+        }
+    }
+}
+
+/// `flatten` returns every value of a map with its dotted key.
+/// Empty tables are kept as a single value.
+pub(crate) fn flatten(values: &Map<String, Value>) -> Vec<(String, Value)> {
+    let mut entries = vec![];
+
+    for (key, value) in values {
+        match value {
+            Value::Object(table) if !table.is_empty() => {
+                for (inner, value) in flatten(table) {
+                    entries.push((format!("{key}.{inner}"), value));
+                }
+            }
+
+            _ => entries.push((key.clone(), value.clone())),
+        }
+    }
+
+    entries
+}
+
+/// `set` sets a dotted key to the given raw value.
+///
+/// The raw value is read as a boolean or a number when the configuration accepts it,
+/// and as text otherwise, so the type of every key is the one of the `Config` struct.
+/// Returns a message if the key is unknown or the value is not valid for it.
+pub(crate) fn set(values: &mut Map<String, Value>, key: &str, raw: &str) -> Result<(), String> {
+    if !is_known_key(key) {
+        return Err(unknown_key(key));
+    }
+
+    let mut candidates = vec![];
+
+    if let Ok(value) = serde_json::from_str::<Value>(raw) {
+        if value.is_boolean() || value.is_number() {
+            candidates.push(value);
+        }
+    }
+
+    candidates.push(Value::from(raw));
+
+    let mut error = String::new();
+
+    for candidate in candidates {
+        let mut attempt = values.clone();
+        insert(&mut attempt, key, candidate);
+
+        match serde_json::from_value::<Config>(Value::Object(attempt.clone())) {
+            Ok(_) => {
+                *values = attempt;
+                return Ok(());
+            }
+            Err(err) => error = err.to_string(),
+        }
+    }
+
+    Err(format!("Invalid value {raw:?} for {key}, {error}"))
+}
+
+/// `insert` inserts a value under a dotted key, creating the tables in between.
+pub(crate) fn insert(values: &mut Map<String, Value>, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((table, rest)) => {
+            let entry = values
+                .entry(table.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+
+            if let Value::Object(inner) = entry {
+                insert(inner, rest, value);
+            }
+        }
+
+        None => {
+            values.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// `get` returns the value under a dotted key.
+pub(crate) fn get<'a>(values: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    match key.split_once('.') {
+        Some((table, rest)) => match values.get(table) {
+            Some(Value::Object(inner)) => get(inner, rest),
+            _ => None,
+        },
+        None => values.get(key),
+    }
+}
+
+/// `remove` removes the value under a dotted key, returns if there was one.
+pub(crate) fn remove(values: &mut Map<String, Value>, key: &str) -> bool {
+    match key.split_once('.') {
+        Some((table, rest)) => match values.get_mut(table) {
+            Some(Value::Object(inner)) => remove(inner, rest),
+            _ => false,
+        },
+        None => values.remove(key).is_some(),
+    }
+}
+
+/// `is_known_key` checks if a dotted key belongs to the configuration.
+/// Keys inside a table that holds arbitrary names, like `lists.work`, are known.
+pub(crate) fn is_known_key(key: &str) -> bool {
+    let defaults = to_map(&Config::default());
+
+    match key.split_once('.') {
+        Some((table, rest)) => match defaults.get(table) {
+            Some(Value::Object(inner)) if inner.is_empty() => !rest.is_empty(),
+            Some(Value::Object(inner)) => inner.contains_key(rest),
+            _ => false,
+        },
+        None => key != "version" && defaults.contains_key(key),
+    }
+}
+
+/// `unknown_key` returns the message for an unknown key, listing the known ones.
+pub(crate) fn unknown_key(key: &str) -> String {
+    match suggestion(key) {
+        Some(suggestion) => format!("Unknown key {key:?}, {suggestion}"),
+        None => format!(
+            "Unknown key {key:?}, known keys are: {}",
+            known_keys().join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// `Dir` is a temporary directory for the config files of a test, removed once dropped.
+    struct Dir(PathBuf);
+
+    impl Dir {
+        fn new(name: &str) -> Dir {
+            let dir = env::temp_dir().join(format!("todo-layers-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Dir(dir)
+        }
+
+        fn write(&self, name: &str, content: &str) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Dir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// `pairs` returns owned `key=value` pairs.
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// `origin` returns the layer the effective value of a key came from.
+    fn origin(layered: &Layered, key: &str) -> Layer {
+        layered
+            .entries()
+            .into_iter()
+            .find(|(entry, _, _)| entry == key)
+            .map(|(_, _, origin)| origin.layer)
+            .unwrap()
+    }
+
+    #[test]
+    fn higher_layers_win() {
+        let dir = Dir::new("precedence");
+        let sources = Sources {
+            system: Some(dir.write(
+                "system.toml",
+                "compact_after = 1\ntrash_retention_days = 1\nbanner = false\nlist_sort = \"name\"\n",
+            )),
+            user: dir.write(
+                "user.toml",
+                "version = 1\ncompact_after = 2\ntrash_retention_days = 2\nbanner = true\n",
+            ),
+            project: Some(dir.write(
+                "project.toml",
+                "compact_after = 3\ntrash_retention_days = 3\n",
+            )),
+            env: pairs(&[("compact_after", "4"), ("trash_retention_days", "4")]),
+            cli: pairs(&[("compact_after", "5")]),
+        };
+
+        let layered = load(&sources);
+
+        assert!(layered.issues().is_empty(), "{:?}", layered.issues());
+        assert_eq!(layered.config.get_compact_after(), 5);
+        assert_eq!(layered.config.get_trash_retention_days(), 4);
+        assert_eq!(layered.get("banner"), Some(&Value::from(true)));
+        assert_eq!(layered.get("list_sort"), Some(&Value::from("name")));
+
+        assert_eq!(origin(&layered, "compact_after"), Layer::Cli);
+        assert_eq!(origin(&layered, "trash_retention_days"), Layer::Environment);
+        assert_eq!(origin(&layered, "banner"), Layer::User);
+        assert_eq!(origin(&layered, "list_sort"), Layer::System);
+        assert_eq!(origin(&layered, "storage"), Layer::Default);
+    }
+
+    #[test]
+    fn missing_files_are_skipped() {
+        let dir = Dir::new("missing");
+        let sources = Sources {
+            system: Some(dir.0.join("system.toml")),
+            user: dir.0.join("user.toml"),
+            project: None,
+            env: vec![],
+            cli: pairs(&[("compact_after", "7")]),
+        };
+
+        let layered = load(&sources);
+
+        assert!(layered.issues().is_empty(), "{:?}", layered.issues());
+        assert_eq!(layered.config.get_compact_after(), 7);
+    }
+
+    #[test]
+    fn invalid_overrides_keep_the_lower_layer() {
+        let dir = Dir::new("invalid");
+        let sources = Sources {
+            user: dir.write("user.toml", "version = 1\ncompact_after = 2\n"),
+            cli: pairs(&[("compact_after", "many"), ("nope", "1")]),
+            ..Sources::default()
+        };
+
+        let layered = load(&sources);
+
+        assert_eq!(layered.issues().len(), 2);
+        assert_eq!(layered.config.get_compact_after(), 2);
+        assert_eq!(origin(&layered, "compact_after"), Layer::User);
+    }
+
+    #[test]
+    fn relative_paths_follow_their_file() {
+        let dir = Dir::new("anchor");
+        let sources = Sources {
+            project: Some(dir.write(
+                "project.toml",
+                "data_path_file = \"lists/task.list\"\n[lists]\nwork = \"~/work.list\"\n",
+            )),
+            ..Sources::default()
+        };
+
+        let layered = load(&sources);
+
+        assert_eq!(
+            layered.get("data_path_file"),
+            Some(&Value::from(
+                dir.0.join("lists/task.list").display().to_string()
+            ))
+        );
+        assert_eq!(layered.get("lists.work"), Some(&Value::from("~/work.list")));
+    }
+
+    #[test]
+    fn environment_variables_map_to_keys() {
+        env::set_var("TODO_TRASH_RETENTION_DAYS", "9");
+        env::set_var("TODO_NOT_A_KEY", "1");
+
+        let values = env_values();
+
+        env::remove_var("TODO_TRASH_RETENTION_DAYS");
+        env::remove_var("TODO_NOT_A_KEY");
+
+        assert!(values.contains(&(String::from("trash_retention_days"), String::from("9"))));
+        assert!(!values.iter().any(|(key, _)| key == "not_a_key"));
+
+        let layered = load(&Sources {
+            env: values,
+            ..Sources::default()
+        });
+
+        let (_, _, origin) = layered
+            .entries()
+            .into_iter()
+            .find(|(key, _, _)| key == "trash_retention_days")
+            .unwrap();
+
+        assert_eq!(origin.to_string(), "env (TODO_TRASH_RETENTION_DAYS)");
+        assert_eq!(layered.config.get_trash_retention_days(), 9);
+    }
+
+    #[test]
+    fn overrides_need_an_equal_sign() {
+        assert_eq!(
+            parse_override(" banner =false"),
+            Ok((String::from("banner"), String::from("false")))
+        );
+        assert_eq!(
+            parse_override("list_template={id}={name}"),
+            Ok((String::from("list_template"), String::from("{id}={name}")))
+        );
+        assert_eq!(
+            parse_override("banner"),
+            Err(String::from("Invalid -c \"banner\", expected KEY=VALUE."))
+        );
+    }
+}
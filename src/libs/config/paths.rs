@@ -0,0 +1,224 @@
+//! A module to resolve the paths written in the configuration.
+//! Paths can start with `~`, use environment variables like `$HOME` or `${HOME}`,
+//! and be relative to the directory of the config file,
+//! so one config file can be shared across machines.
+
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+/// `expand` expands a leading `~` and the environment variables of a path.
+/// Returns a message if the home directory or a variable is not found.
+///
+/// # Example
+///
+/// `~/todo/task.list` and `$HOME/todo/task.list` both become `/home/me/todo/task.list`.
+pub fn expand(path: &Path) -> Result<PathBuf, String> {
+    let text = path.to_string_lossy();
+    let text = expand_variables(&text)?;
+
+    match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            let home = dirs::home_dir().ok_or("Home directory not found")?;
+            Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+        }
+
+        _ => Ok(PathBuf::from(text)),
+    }
+}
+
+/// `resolve` expands a path and makes it absolute,
+/// a relative path is taken from the given base directory.
+/// Returns a message if the path cannot be expanded.
+pub fn resolve(path: &Path, base: Option<&Path>) -> Result<PathBuf, String> {
+    let path = expand(path)?;
+
+    match base {
+        Some(base) if path.is_relative() => Ok(normalize(&base.join(path))),
+        _ => Ok(path),
+    }
+}
+
+/// `from_current_dir` takes a path typed on the command line from the current directory,
+/// so it points to the same file once saved in the config file.
+/// Anchored paths are kept as they are, see `is_anchored`.
+pub fn from_current_dir(path: &Path) -> PathBuf {
+    match env::current_dir() {
+        Ok(dir) if !is_anchored(path) => normalize(&dir.join(path)),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `is_anchored` checks if a path does not depend on the directory it is read from,
+/// that is an absolute path or one starting with `~` or a variable.
+pub fn is_anchored(path: &Path) -> bool {
+    let text = path.to_string_lossy();
+    path.is_absolute() || text.starts_with('~') || text.starts_with('$')
+}
+
+/// `expand_variables` replaces `$NAME` and `${NAME}` with the value of the variable.
+fn expand_variables(text: &str) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let (name, after) = match rest.strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) => (&inner[..end], &inner[end + 1..]),
+                None => return Err(format!("Missing '}}' in {text:?}")),
+            },
+
+            None => {
+                let end = rest
+                    .find(|char: char| !(char.is_alphanumeric() || char == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+
+        if name.is_empty() {
+            expanded.push('$');
+        } else {
+            match env::var(name) {
+                Ok(value) => expanded.push_str(&value),
+                Err(_) => return Err(format!("Environment variable {name} is not set")),
+            }
+        }
+
+        rest = after;
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// `normalize` removes the `.` and `..` components of a path, without touching the disk.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn home() {
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(expand(Path::new("~")), Ok(home.clone()));
+        assert_eq!(
+            expand(Path::new("~/todo/task.list")),
+            Ok(home.join("todo/task.list"))
+        );
+        assert_eq!(
+            expand(Path::new("~other/task.list")),
+            Ok(PathBuf::from("~other/task.list"))
+        );
+        assert_eq!(
+            expand(Path::new("todo/~/task.list")),
+            Ok(PathBuf::from("todo/~/task.list"))
+        );
+    }
+
+    #[test]
+    fn variables() {
+        env::set_var("TODO_PATHS_TEST_DIR", "/srv/todo");
+
+        assert_eq!(
+            expand_variables("$TODO_PATHS_TEST_DIR/task.list"),
+            Ok(String::from("/srv/todo/task.list"))
+        );
+        assert_eq!(
+            expand_variables("${TODO_PATHS_TEST_DIR}lists/work.list"),
+            Ok(String::from("/srv/todolists/work.list"))
+        );
+        assert_eq!(
+            expand_variables("a/$/b and ${TODO_PATHS_TEST_DIR}"),
+            Ok(String::from("a/$/b and /srv/todo"))
+        );
+        assert_eq!(expand_variables("plain"), Ok(String::from("plain")));
+
+        env::remove_var("TODO_PATHS_TEST_DIR");
+    }
+
+    #[test]
+    fn undefined_variables() {
+        assert_eq!(
+            expand_variables("$TODO_PATHS_TEST_UNSET/task.list"),
+            Err(String::from(
+                "Environment variable TODO_PATHS_TEST_UNSET is not set"
+            ))
+        );
+        assert_eq!(
+            expand_variables("${TODO_PATHS_TEST_UNSET"),
+            Err(String::from("Missing '}' in \"${TODO_PATHS_TEST_UNSET\""))
+        );
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(
+            normalize(Path::new("/a/./b/../c/task.list")),
+            PathBuf::from("/a/c/task.list")
+        );
+        assert_eq!(
+            normalize(Path::new("/a/../../task.list")),
+            PathBuf::from("/task.list")
+        );
+        assert_eq!(normalize(Path::new("a/b/..")), PathBuf::from("a"));
+    }
+
+    #[test]
+    fn relative_paths_are_anchored_to_the_base() {
+        let base = Path::new("/home/me/.config/todo");
+
+        assert_eq!(
+            resolve(Path::new("task.list"), Some(base)),
+            Ok(PathBuf::from("/home/me/.config/todo/task.list"))
+        );
+        assert_eq!(
+            resolve(Path::new("../lists/./work.list"), Some(base)),
+            Ok(PathBuf::from("/home/me/.config/lists/work.list"))
+        );
+        assert_eq!(
+            resolve(Path::new("/srv/task.list"), Some(base)),
+            Ok(PathBuf::from("/srv/task.list"))
+        );
+        assert_eq!(
+            resolve(Path::new("task.list"), None),
+            Ok(PathBuf::from("task.list"))
+        );
+    }
+
+    #[test]
+    fn anchored_paths() {
+        assert!(is_anchored(Path::new("/srv/task.list")));
+        assert!(is_anchored(Path::new("~/task.list")));
+        assert!(is_anchored(Path::new("$HOME/task.list")));
+        assert!(!is_anchored(Path::new("task.list")));
+        assert!(!is_anchored(Path::new("../task.list")));
+
+        assert_eq!(
+            from_current_dir(Path::new("/srv/task.list")),
+            PathBuf::from("/srv/task.list")
+        );
+        assert_eq!(
+            from_current_dir(Path::new("lists/../task.list")),
+            env::current_dir().unwrap().join("task.list")
+        );
+    }
+}
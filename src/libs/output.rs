@@ -0,0 +1,423 @@
+//! A module to handle how results and errors are written.
+//! Besides the human format, results can be written as JSON, YAML or TSV
+//! so scripts and editor plugins can read them.
+//! Structured formats have no banner and no colors,
+//! colors are also turned off by `--color never`, `NO_COLOR` or when the output is not a terminal,
+//! and errors are written to the standard output with their exit code.
+//! The exit codes are described in `code`.
+
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::process;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde_any::Format;
+use serde_json::{json, Value};
+
+use crate::libs::render::theme::{paint, theme};
+use crate::libs::tasks::task::Task;
+use crate::libs::tasks::Tasks;
+
+pub mod code;
+
+/// `OutputFormat` is how results and errors are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Text with colors, for people.
+    #[default]
+    Human,
+    /// A JSON document.
+    Json,
+    /// A YAML document.
+    Yaml,
+    /// Tab separated values, tables have a header row.
+    Tsv,
+}
+
+/// `FromStr` is an implementation to parse the output format from the CLI.
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!(
+                "unknown output format {format:?}, expected human, json, yaml or tsv"
+            )),
+        }
+    }
+}
+
+/// `Display` is an implementation to write the output format as given on the CLI.
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OutputFormat::Human => "human",
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Tsv => "tsv",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The output format of this run.
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// `set_format` sets the output format for this run, only the first call counts.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// `format` returns the output format of this run, human if none was set.
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// `is_human` checks if the output is meant for people.
+pub fn is_human() -> bool {
+    format() == OutputFormat::Human
+}
+
+/// `ColorChoice` is when the output has colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors if the output is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always colors, in the human format.
+    Always,
+    /// Never colors.
+    Never,
+}
+
+/// `FromStr` is an implementation to parse the color choice from the CLI.
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(choice: &str) -> Result<Self, Self::Err> {
+        match choice {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "unknown color choice {choice:?}, expected auto, always or never"
+            )),
+        }
+    }
+}
+
+/// `set_color` turns the colors on or off for this run.
+/// Structured formats never have colors.
+pub fn set_color(choice: ColorChoice) {
+    let enabled = is_human()
+        && match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && io::stdout().is_terminal()
+            }
+        };
+
+    colored::control::set_override(enabled);
+}
+
+/// `TaskRecord` is a task as written in the structured formats, with its number.
+#[derive(Serialize, Debug, Clone)]
+pub struct TaskRecord {
+    pub id: usize,
+    pub name: String,
+    pub description: String,
+    pub completed: bool,
+}
+
+/// `TaskRecord` is an implementation of the task record.
+impl TaskRecord {
+    /// `new` creates the record of the task with the given number.
+    pub fn new(id: usize, task: &Task) -> TaskRecord {
+        TaskRecord {
+            id,
+            name: task.name.clone(),
+            description: task.description.clone(),
+            completed: task.get_is_completed(),
+        }
+    }
+}
+
+/// `records` returns the records of all the tasks, in order.
+pub fn records(tasks: &Tasks) -> Vec<TaskRecord> {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(id, task)| TaskRecord::new(id, task))
+        .collect()
+}
+
+/// `report` writes the result of a command.
+/// In the human format `human` is called to print it,
+/// otherwise `value` is written in the structured format.
+pub fn report<T: Serialize>(value: &T, human: impl FnOnce()) {
+    if is_human() {
+        human();
+    } else {
+        emit(value);
+    }
+}
+
+/// `emit` writes a value to the standard output in the structured format of this run.
+/// In the human format, it is written as JSON.
+pub fn emit<T: Serialize>(value: &T) {
+    let value = match serde_json::to_value(value) {
+        Ok(value) => value,
+        Err(err) => fail(code::INTERNAL, format!("Serializing, {err}")),
+    };
+
+    let text = match format() {
+        OutputFormat::Yaml => {
+            serde_any::to_string(&value, Format::Yaml).map_err(|err| err.to_string())
+        }
+        OutputFormat::Tsv => Ok(to_tsv(&value)),
+        OutputFormat::Human | OutputFormat::Json => {
+            serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+        }
+    };
+
+    match text {
+        Ok(text) => println!("{}", text.trim_end()),
+        Err(err) => {
+            eprintln!("{} : Serializing, {}", paint("ERROR", &theme().error), err);
+            process::exit(code::INTERNAL);
+        }
+    }
+}
+
+/// `confirm` asks a yes or no question on the terminal, after showing the given lines.
+/// Both are written to the standard error, so the result of the command is kept apart.
+/// Returns None when the standard input is not a terminal, as there is nobody to ask.
+pub fn confirm(lines: &[String], question: &str) -> Option<bool> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    for line in lines {
+        eprintln!("  {line}");
+    }
+
+    eprint!("{question} [y/N] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+
+    if io::stdin().read_line(&mut answer).is_err() {
+        return Some(false);
+    }
+
+    Some(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// `fail` writes an error and exits with the given code.
+/// Refer to `error_with` for how it is written.
+pub fn fail(code: i32, message: impl Display) -> ! {
+    fail_with("ERROR", code, message)
+}
+
+/// `fail_with` writes an error with the given label and exits with the given code.
+/// The locks still held are released by the operating system on exit.
+pub fn fail_with(label: &str, code: i32, message: impl Display) -> ! {
+    error_with(label, code, message);
+
+    process::exit(code);
+}
+
+/// `error` writes an error with the code it would exit with, but keeps running.
+/// Used where the program goes on after an error, like the shell.
+pub fn error(code: i32, message: impl Display) {
+    error_with("ERROR", code, message)
+}
+
+/// `error_with` writes an error with the given label.
+/// In the human format, it is written to the standard error as `<label> : <message>`.
+/// In the structured formats, it is written to the standard output
+/// as an `error` with the code, its name and the message.
+pub fn error_with(label: &str, code: i32, message: impl Display) {
+    if is_human() {
+        eprintln!("{} : {}", paint(label, &theme().error), message);
+    } else {
+        emit(&json!({
+            "error": {
+                "code": code,
+                "kind": code::kind(code),
+                "message": message.to_string(),
+            }
+        }));
+    }
+}
+
+/// `to_tsv` writes a value as tab separated values.
+///
+/// A result made of a single list of tables, like the tasks of `todo list`,
+/// becomes a header row followed by one row per entry.
+/// Otherwise the other values come first as `key<TAB>value` rows with dotted keys,
+/// then every list of tables after a blank line and a `[key]` line, even when empty.
+/// Tabs, new lines and backslashes inside values are escaped.
+fn to_tsv(value: &Value) -> String {
+    if let Value::Object(table) = value {
+        if let (1, Some(rows)) = (table.len(), table.values().next().and_then(as_table)) {
+            return tsv_table(rows).join("\n");
+        }
+    }
+
+    let mut lines = vec![];
+    let mut tables = vec![];
+    tsv_rows(value, "", &mut lines, &mut tables);
+
+    for (key, rows) in tables {
+        lines.push(String::new());
+        lines.push(format!("[{key}]"));
+        lines.extend(tsv_table(rows));
+    }
+
+    lines.join("\n")
+}
+
+/// `as_table` returns the rows of a value if it is a list of tables, empty or not.
+fn as_table(value: &Value) -> Option<&Vec<Value>> {
+    match value {
+        Value::Array(rows) if rows.iter().all(Value::is_object) => Some(rows),
+        _ => None,
+    }
+}
+
+/// `tsv_rows` appends the rows of a value under the given key prefix,
+/// keeping the lists of tables apart with their dotted keys.
+fn tsv_rows<'a>(
+    value: &'a Value,
+    prefix: &str,
+    lines: &mut Vec<String>,
+    tables: &mut Vec<(String, &'a Vec<Value>)>,
+) {
+    let key = prefix.trim_end_matches('.');
+
+    match value {
+        Value::Object(table) => {
+            for (key, value) in table {
+                tsv_rows(value, &format!("{prefix}{key}."), lines, tables);
+            }
+        }
+
+        Value::Array(items) => match as_table(value) {
+            Some(rows) => tables.push((key.to_string(), rows)),
+            None => {
+                let cells: Vec<String> = items.iter().map(tsv_cell).collect();
+                lines.push(format!("{key}\t{}", cells.join("\t")));
+            }
+        },
+
+        value => lines.push(format!("{key}\t{}", tsv_cell(value))),
+    }
+}
+
+/// `tsv_table` returns the header row and one row per entry of a list of tables,
+/// the header taken from the first entry. An empty list has no rows.
+fn tsv_table(rows: &[Value]) -> Vec<String> {
+    let Some(Value::Object(first)) = rows.first() else {
+        return vec![];
+    };
+
+    let header: Vec<&str> = first.keys().map(String::as_str).collect();
+    let mut lines = vec![header.join("\t")];
+
+    for row in rows {
+        let cells: Vec<String> = header
+            .iter()
+            .map(|key| row.get(*key).map(tsv_cell).unwrap_or_default())
+            .collect();
+        lines.push(cells.join("\t"));
+    }
+
+    lines
+}
+
+/// `tsv_cell` returns a value as a single escaped cell.
+fn tsv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `records` returns the TSV of the given tasks, as `todo list` writes them.
+    fn records(tasks: &[Task]) -> String {
+        let records: Vec<TaskRecord> = tasks
+            .iter()
+            .enumerate()
+            .map(|(number, task)| TaskRecord::new(number, task))
+            .collect();
+
+        to_tsv(&json!({ "tasks": records }))
+    }
+
+    #[test]
+    fn tasks_are_a_table() {
+        let tasks = [
+            Task::new(String::from("Buy milk"), String::new()),
+            Task::new(String::from("Call"), String::from("About the trip")),
+        ];
+
+        assert_eq!(
+            records(&tasks),
+            "id\tname\tdescription\tcompleted\n0\tBuy milk\t\tfalse\n1\tCall\tAbout the trip\tfalse"
+        );
+    }
+
+    #[test]
+    fn tabs_and_new_lines_are_escaped() {
+        let tasks = [Task::new(
+            String::from("Tab\there"),
+            String::from("Two\nlines\r\nand a \\ backslash"),
+        )];
+
+        let tsv = records(&tasks);
+        let lines: Vec<&str> = tsv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].split('\t').count(), 4);
+        assert_eq!(
+            lines[1],
+            "0\tTab\\there\tTwo\\nlines\\r\\nand a \\\\ backslash\tfalse"
+        );
+    }
+
+    #[test]
+    fn values_come_before_labelled_tables() {
+        let value = json!({
+            "action": "done",
+            "tasks": [{ "id": 1, "name": "a\tb" }],
+            "unchanged": [2, 3],
+            "trash": { "tasks": [] },
+        });
+
+        assert_eq!(
+            to_tsv(&value),
+            "action\tdone\nunchanged\t2\t3\n\n[tasks]\nid\tname\n1\ta\\tb\n\n[trash.tasks]"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! The exit codes of the program, one per class of failure.
+//!
+//! They are a contract for scripts: every command exits with the code of the class
+//! of its failure, and a code keeps its meaning between versions.
+//! The values come from `sysexits.h`, and are also written as the `kind` of structured errors.
+//!
+//! | Code | Kind            | Meaning                                                  |
+//! |------|-----------------|----------------------------------------------------------|
+//! | 0    | `ok`            | The command succeeded.                                   |
+//! | 64   | `invalid_input` | The command line or a value given in it is not valid.    |
+//! | 65   | `parse_error`   | A data file or an imported file cannot be parsed.        |
+//! | 66   | `not_found`     | A task, a list or a file does not exist.                 |
+//! | 70   | `internal`      | An internal error, please report it.                     |
+//! | 73   | `conflict`      | Something with the same name or path already exists.     |
+//! | 74   | `io_error`      | A file cannot be read or written.                        |
+//! | 75   | `lock_busy`     | The data file is locked by another process, retry later. |
+//! | 78   | `config_error`  | The configuration is not valid.                          |
+
+use std::io::{self, ErrorKind};
+
+/// The command succeeded.
+pub const OK: i32 = exitcode::OK;
+
+/// The command line or a value given in it is not valid.
+pub const INVALID_INPUT: i32 = exitcode::USAGE;
+
+/// A data file or an imported file cannot be parsed.
+pub const PARSE_ERROR: i32 = exitcode::DATAERR;
+
+/// A task, a list or a file does not exist.
+pub const NOT_FOUND: i32 = exitcode::NOINPUT;
+
+/// An internal error, please report it.
+pub const INTERNAL: i32 = exitcode::SOFTWARE;
+
+/// Something with the same name or path already exists.
+pub const CONFLICT: i32 = exitcode::CANTCREAT;
+
+/// A file cannot be read or written.
+pub const IO_ERROR: i32 = exitcode::IOERR;
+
+/// The data file is locked by another process, the command can be tried again later.
+pub const LOCK_BUSY: i32 = exitcode::TEMPFAIL;
+
+/// The configuration is not valid.
+pub const CONFIG_ERROR: i32 = exitcode::CONFIG;
+
+/// Every exit code with its kind and what it means, in order.
+pub const ALL: [(i32, &str, &str); 9] = [
+    (OK, "ok", "The command succeeded."),
+    (
+        INVALID_INPUT,
+        "invalid_input",
+        "The command line or a value given in it is not valid.",
+    ),
+    (
+        PARSE_ERROR,
+        "parse_error",
+        "A data file or an imported file cannot be parsed.",
+    ),
+    (
+        NOT_FOUND,
+        "not_found",
+        "A task, a list or a file does not exist.",
+    ),
+    (INTERNAL, "internal", "An internal error, please report it."),
+    (
+        CONFLICT,
+        "conflict",
+        "Something with the same name or path already exists.",
+    ),
+    (IO_ERROR, "io_error", "A file cannot be read or written."),
+    (
+        LOCK_BUSY,
+        "lock_busy",
+        "The data file is locked by another process, retry later.",
+    ),
+    (
+        CONFIG_ERROR,
+        "config_error",
+        "The configuration is not valid.",
+    ),
+];
+
+/// `kind` returns the kind of an exit code, as written in structured errors.
+pub fn kind(code: i32) -> &'static str {
+    ALL.iter()
+        .find(|(known, _, _)| *known == code)
+        .map_or("internal", |(_, kind, _)| kind)
+}
+
+/// `of_io` returns the exit code for a failed file operation.
+pub fn of_io(err: &io::Error) -> i32 {
+    match err.kind() {
+        ErrorKind::NotFound => NOT_FOUND,
+        ErrorKind::AlreadyExists => CONFLICT,
+        ErrorKind::WouldBlock => LOCK_BUSY,
+        ErrorKind::InvalidData => PARSE_ERROR,
+        _ => IO_ERROR,
+    }
+}
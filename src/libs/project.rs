@@ -0,0 +1,90 @@
+//! A module to handle the project-local task lists.
+//! Like `.git`, a `.todo` file or directory is looked up from the
+//! current directory upwards, and its tasks are used instead of the
+//! global data path.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::libs::tasks::{self, Tasks};
+
+/// The name of the project-local data file or directory.
+pub const PROJECT_FILE: &str = ".todo";
+
+/// The name of the data file inside a `.todo` directory.
+const PROJECT_DATA_FILE: &str = "task.list";
+
+/// The name of the project config file, next to `.todo` or inside a `.todo` directory.
+pub const PROJECT_CONFIG_FILE: &str = ".todo.config";
+
+/// The name of the config file inside a `.todo` directory.
+const PROJECT_DIR_CONFIG_FILE: &str = "todo.config";
+
+/// `data_path` returns the data path of a `.todo` entry.
+/// A file is the data itself, a directory holds it as `task.list`.
+fn data_path(entry: PathBuf) -> PathBuf {
+    if entry.is_dir() {
+        entry.join(PROJECT_DATA_FILE)
+    } else {
+        entry
+    }
+}
+
+/// `discover` looks for a `.todo` file or directory in the given directory
+/// and its ancestors, returning the data path of the closest one.
+///
+/// # Example
+///
+/// ```
+/// use std::env;
+/// use todo::libs::project::discover;
+///
+/// if let Some(path) = discover(&env::current_dir().unwrap()) {
+///     println!("Using {path:?}");
+/// }
+/// ```
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|entry| entry.exists())
+        .map(data_path)
+}
+
+/// `discover_config` looks for a project config in the given directory and its ancestors,
+/// either `.todo/todo.config` or `.todo.config`, returning the closest one.
+pub fn discover_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .flat_map(|dir| {
+            [
+                dir.join(PROJECT_FILE).join(PROJECT_DIR_CONFIG_FILE),
+                dir.join(PROJECT_CONFIG_FILE),
+            ]
+        })
+        .find(|path| path.is_file())
+}
+
+/// `init` creates an empty project list in the given directory,
+/// as a `.todo` file or as a `.todo` directory, and returns its data path.
+/// If the directory already has a `.todo`, an error of kind `AlreadyExists` is returned.
+pub fn init(dir: &Path, as_directory: bool) -> io::Result<PathBuf> {
+    let entry = dir.join(PROJECT_FILE);
+
+    if entry.exists() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{entry:?} already exists"),
+        ));
+    }
+
+    if as_directory {
+        fs::create_dir(&entry)?;
+    }
+
+    let path = data_path(entry);
+    tasks::io::save(&Tasks::new(), &path);
+
+    Ok(path)
+}
@@ -0,0 +1,156 @@
+//! A module to turn tasks into text.
+//! Every view of the tasks is a `Renderer`, so the CLI and library
+//! consumers can choose how the tasks look without touching the model.
+
+use std::str::FromStr;
+
+use crate::libs::output::TaskRecord;
+use crate::libs::render::table::Table;
+use crate::libs::tasks::order::Sort;
+use crate::libs::tasks::task::Task;
+use crate::libs::tasks::Tasks;
+
+pub mod table;
+pub mod template;
+pub mod theme;
+
+/// `Entry` is a task together with its number in the list.
+pub type Entry<'a> = (usize, &'a Task);
+
+/// `Renderer` turns a set of numbered tasks into text.
+pub trait Renderer {
+    /// `render` returns the text of the given tasks, to be printed as a whole.
+    fn render(&self, entries: &[Entry]) -> String;
+}
+
+/// `entries` returns every task of the list with its number, in the given order.
+pub fn entries(tasks: &Tasks, sort: Sort) -> Vec<Entry<'_>> {
+    sort.numbers(tasks)
+        .into_iter()
+        .filter_map(|number| tasks.get(number).map(|task| (number, task)))
+        .collect()
+}
+
+/// `View` is one of the built-in renderers, as chosen on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum View {
+    /// Every field of a task, one per line.
+    #[default]
+    Long,
+    /// One line per task.
+    Short,
+    /// Aligned columns, one row per task.
+    Table,
+    /// The tasks as branches of a tree, with the description below the name.
+    Tree,
+    /// A JSON array of tasks.
+    Json,
+}
+
+/// `FromStr` is an implementation to parse the view from the CLI.
+impl FromStr for View {
+    type Err = String;
+
+    fn from_str(view: &str) -> Result<Self, Self::Err> {
+        match view {
+            "long" => Ok(View::Long),
+            "short" => Ok(View::Short),
+            "table" => Ok(View::Table),
+            "tree" => Ok(View::Tree),
+            "json" => Ok(View::Json),
+            _ => Err(format!(
+                "unknown format {view:?}, expected long, short, table, tree or json"
+            )),
+        }
+    }
+}
+
+/// `View` is an implementation of the view.
+impl View {
+    /// `renderer` returns the renderer of the view.
+    pub fn renderer(&self) -> Box<dyn Renderer> {
+        match self {
+            View::Long => Box::new(Long),
+            View::Short => Box::new(Short),
+            View::Table => Box::new(Table::default()),
+            View::Tree => Box::new(Tree),
+            View::Json => Box::new(Json),
+        }
+    }
+}
+
+/// `Long` renders every field of a task, one per line.
+pub struct Long;
+
+/// `Renderer` is an implementation of the long view.
+impl Renderer for Long {
+    fn render(&self, entries: &[Entry]) -> String {
+        entries
+            .iter()
+            .map(|(number, task)| format!("TASK NUMBER : {number}\n{task}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `Short` renders one line per task, with its name and status.
+pub struct Short;
+
+/// `Renderer` is an implementation of the short view.
+impl Renderer for Short {
+    fn render(&self, entries: &[Entry]) -> String {
+        entries
+            .iter()
+            .map(|(number, task)| {
+                format!("Task : {number} - {} - {}", task.name, theme::status(task))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `Tree` renders the tasks as branches, with the description as a leaf below the name.
+pub struct Tree;
+
+/// `Renderer` is an implementation of the tree view.
+impl Renderer for Tree {
+    fn render(&self, entries: &[Entry]) -> String {
+        let mut lines = vec![];
+
+        for (position, (number, task)) in entries.iter().enumerate() {
+            let last = position + 1 == entries.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            lines.push(format!(
+                "{branch}{number} {} {}",
+                theme::status(task),
+                task.name
+            ));
+
+            if !task.description.is_empty() {
+                lines.push(format!("{indent}└── {}", task.description));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// `Json` renders the tasks as a JSON array of task records.
+pub struct Json;
+
+/// `Renderer` is an implementation of the JSON view.
+impl Renderer for Json {
+    fn render(&self, entries: &[Entry]) -> String {
+        let records: Vec<TaskRecord> = entries
+            .iter()
+            .map(|(number, task)| TaskRecord::new(*number, task))
+            .collect();
+
+        serde_json::to_string_pretty(&records).unwrap_or_default()
+    }
+}
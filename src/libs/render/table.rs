@@ -0,0 +1,412 @@
+//! A module to render the tasks as a table.
+//! The columns and their order can be chosen,
+//! and the table is fitted to a width by truncating or wrapping the text columns.
+
+use std::str::FromStr;
+
+use crate::libs::render::{theme, Entry, Renderer};
+use crate::libs::tasks::task::Task;
+
+/// The space between two columns.
+const GAP: &str = "  ";
+
+/// The narrowest a text column is made to fit the width.
+const MIN_TEXT_WIDTH: usize = 8;
+
+/// `Column` is a field of the task shown in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// The number of the task.
+    Id,
+    /// Whether the task is completed.
+    Status,
+    /// The name of the task.
+    Name,
+    /// The description of the task.
+    Description,
+}
+
+/// The columns shown when none are chosen.
+pub const DEFAULT_COLUMNS: [Column; 4] = [
+    Column::Id,
+    Column::Status,
+    Column::Name,
+    Column::Description,
+];
+
+/// `FromStr` is an implementation to parse a column name.
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(column: &str) -> Result<Self, Self::Err> {
+        match column.trim().to_lowercase().as_str() {
+            "id" => Ok(Column::Id),
+            "status" => Ok(Column::Status),
+            "name" => Ok(Column::Name),
+            "description" => Ok(Column::Description),
+            "priority" | "due" | "tags" => Err(format!(
+                "tasks have no {} yet, expected id, status, name or description",
+                column.trim().to_lowercase()
+            )),
+            _ => Err(format!(
+                "unknown column {column:?}, expected id, status, name or description"
+            )),
+        }
+    }
+}
+
+/// `Column` is an implementation of the column.
+impl Column {
+    /// `header` returns the title of the column.
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Status => "STATUS",
+            Column::Name => "NAME",
+            Column::Description => "DESCRIPTION",
+        }
+    }
+
+    /// `cell` returns the text of the column for a task.
+    fn cell(&self, number: usize, task: &Task) -> String {
+        match self {
+            Column::Id => number.to_string(),
+            Column::Status => task.completed_symbol(),
+            Column::Name => task.name.clone(),
+            Column::Description => task.description.clone(),
+        }
+    }
+
+    /// `is_text` checks if the column holds free text, which can be truncated or wrapped.
+    fn is_text(&self) -> bool {
+        matches!(self, Column::Name | Column::Description)
+    }
+}
+
+/// `parse_columns` parses a comma separated list of column names, like `id,status,name`.
+/// Returns a message if a name is unknown or the list is empty.
+pub fn parse_columns(columns: &str) -> Result<Vec<Column>, String> {
+    let columns = columns
+        .split(',')
+        .filter(|column| !column.trim().is_empty())
+        .map(Column::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if columns.is_empty() {
+        return Err(String::from("no columns given"));
+    }
+
+    Ok(columns)
+}
+
+/// `Overflow` is what happens to the text that does not fit in its column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Cut the text, ending it with `…`.
+    #[default]
+    Truncate,
+    /// Continue the text on the next lines.
+    Wrap,
+}
+
+/// `Table` renders the tasks as aligned columns with a header row.
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    width: Option<usize>,
+    overflow: Overflow,
+}
+
+/// `Default` is an implementation of the table with every column and no width limit.
+impl Default for Table {
+    fn default() -> Self {
+        Table::new(DEFAULT_COLUMNS.to_vec(), None, Overflow::default())
+    }
+}
+
+/// `Table` is an implementation of the table.
+impl Table {
+    /// `new` creates a table of the given columns, fitted to the given width if any.
+    pub fn new(columns: Vec<Column>, width: Option<usize>, overflow: Overflow) -> Table {
+        Table {
+            columns,
+            width,
+            overflow,
+        }
+    }
+
+    /// `widths` returns the width of every column,
+    /// shrinking the widest text columns until the table fits the width.
+    fn widths(&self, rows: &[Vec<String>]) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .map(|column| column.header().chars().count())
+            .collect();
+
+        for row in rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let Some(limit) = self.width else {
+            return widths;
+        };
+
+        let gaps = GAP.len() * self.columns.len().saturating_sub(1);
+
+        while widths.iter().sum::<usize>() + gaps > limit {
+            let widest = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .filter(|(_, (column, width))| column.is_text() && **width > MIN_TEXT_WIDTH)
+                .max_by_key(|(_, (_, width))| **width)
+                .map(|(position, _)| position);
+
+            match widest {
+                Some(position) => widths[position] -= 1,
+                None => break,
+            }
+        }
+
+        widths
+    }
+
+    /// `lines` returns the lines of a cell fitted to the given width.
+    fn lines(&self, cell: &str, width: usize) -> Vec<String> {
+        if cell.chars().count() <= width {
+            return vec![cell.to_string()];
+        }
+
+        match self.overflow {
+            Overflow::Truncate => {
+                let cut: String = cell.chars().take(width.saturating_sub(1)).collect();
+                vec![format!("{cut}…")]
+            }
+            Overflow::Wrap => wrap(cell, width),
+        }
+    }
+}
+
+/// `Renderer` is an implementation of the table view.
+impl Renderer for Table {
+    fn render(&self, entries: &[Entry]) -> String {
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| column.header().to_string())
+            .collect();
+
+        let rows: Vec<Vec<String>> = entries
+            .iter()
+            .map(|(number, task)| {
+                self.columns
+                    .iter()
+                    .map(|column| column.cell(*number, task).replace(['\t', '\n'], " "))
+                    .collect()
+            })
+            .collect();
+
+        let widths = self.widths(&rows);
+        let theme = theme::theme();
+        let mut lines = vec![];
+
+        for (position, row) in std::iter::once(&header).chain(&rows).enumerate() {
+            let style = |column: &Column| -> &str {
+                match (position.checked_sub(1), column) {
+                    (None, _) => &theme.header,
+                    (Some(index), Column::Status) if entries[index].1.get_is_completed() => {
+                        &theme.completed
+                    }
+                    (Some(_), Column::Status) => &theme.pending,
+                    _ => "",
+                }
+            };
+
+            let cells: Vec<Vec<String>> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| self.lines(cell, *width))
+                .collect();
+
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+            for line in 0..height {
+                let text = cells
+                    .iter()
+                    .zip(&widths)
+                    .zip(&self.columns)
+                    .map(|((cell, width), column)| {
+                        let part = cell.get(line).map(String::as_str).unwrap_or("");
+                        let padding = width.saturating_sub(part.chars().count());
+                        format!(
+                            "{}{}",
+                            theme::paint(part, style(column)),
+                            " ".repeat(padding)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(GAP);
+
+                lines.push(text.trim_end().to_string());
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// `wrap` splits a text into lines of the given width, breaking between words when possible.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+
+        loop {
+            let used = line.chars().count();
+            let needed = word.chars().count() + usize::from(used > 0);
+
+            if used + needed <= width {
+                if used > 0 {
+                    line.push(' ');
+                }
+                line.push_str(&word);
+                break;
+            }
+
+            if used > 0 {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(head);
+
+            if word.is_empty() {
+                break;
+            }
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `rows` returns rows of a name and a description column.
+    fn rows(cells: &[(&str, &str)]) -> Vec<Vec<String>> {
+        cells
+            .iter()
+            .map(|(name, description)| vec![name.to_string(), description.to_string()])
+            .collect()
+    }
+
+    /// `text_table` returns a table of the name and the description, fitted to a width.
+    fn text_table(width: Option<usize>, overflow: Overflow) -> Table {
+        Table::new(vec![Column::Name, Column::Description], width, overflow)
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(
+            parse_columns("id, Status,,name"),
+            Ok(vec![Column::Id, Column::Status, Column::Name])
+        );
+        assert_eq!(parse_columns(" , "), Err(String::from("no columns given")));
+        assert!(parse_columns("id,size").is_err());
+        assert_eq!(
+            parse_columns("name,due"),
+            Err(String::from(
+                "tasks have no due yet, expected id, status, name or description"
+            ))
+        );
+    }
+
+    #[test]
+    fn widths_fit_the_content() {
+        let table = text_table(None, Overflow::Truncate);
+
+        assert_eq!(table.widths(&rows(&[])), vec![4, 11]);
+        assert_eq!(
+            table.widths(&rows(&[("Buy milk", ""), ("Call", "About the trip")])),
+            vec![8, 14]
+        );
+    }
+
+    #[test]
+    fn widths_count_characters() {
+        let table = text_table(None, Overflow::Truncate);
+
+        assert_eq!(
+            table.widths(&rows(&[("Café crème", "Ça va, très bien")])),
+            vec![10, 16]
+        );
+    }
+
+    #[test]
+    fn widths_shrink_the_widest_text_column() {
+        let table = text_table(Some(30), Overflow::Truncate);
+        let rows = rows(&[("A name of twenty chars", "A short one")]);
+
+        assert_eq!(table.widths(&rows), vec![17, 11]);
+    }
+
+    #[test]
+    fn widths_keep_a_minimum() {
+        let table = Table::new(
+            vec![Column::Id, Column::Status, Column::Name],
+            Some(5),
+            Overflow::Truncate,
+        );
+        let rows = vec![vec![
+            String::from("12"),
+            String::from("✓"),
+            String::from("A rather long name"),
+        ]];
+
+        assert_eq!(table.widths(&rows), vec![2, 6, MIN_TEXT_WIDTH]);
+    }
+
+    #[test]
+    fn truncation() {
+        let table = text_table(None, Overflow::Truncate);
+
+        assert_eq!(table.lines("Buy milk", 8), vec!["Buy milk"]);
+        assert_eq!(table.lines("Buy milk today", 8), vec!["Buy mil…"]);
+        assert_eq!(table.lines("Crème brûlée", 6), vec!["Crème…"]);
+    }
+
+    #[test]
+    fn wrapping() {
+        let table = text_table(None, Overflow::Wrap);
+
+        assert_eq!(
+            table.lines("Buy milk and bread", 9),
+            vec!["Buy milk", "and bread"]
+        );
+        assert_eq!(
+            wrap("Supercalifragilistic", 8),
+            vec!["Supercal", "ifragili", "stic"]
+        );
+        assert_eq!(
+            wrap("a Supercalifragilistic", 8),
+            vec!["a", "Supercal", "ifragili", "stic"]
+        );
+        assert_eq!(wrap("Crème brûlée", 6), vec!["Crème", "brûlée"]);
+        assert_eq!(wrap("", 5), vec![""]);
+        assert_eq!(wrap("abc", 0), vec!["a", "b", "c"]);
+    }
+}
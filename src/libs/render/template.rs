@@ -0,0 +1,436 @@
+//! A module to render the tasks with a user defined template.
+//!
+//! A template is text with placeholders, rendered once per task:
+//!
+//! - `{field}` is replaced by a field of the task, one of `id`, `name`, `description`,
+//!   `status` (`✓` or `✗`), `completed` (`true` or `false`)
+//!   and `completed_at` (seconds since the UNIX epoch, empty if not completed).
+//! - `{field:>3}` aligns the field in a width, to the right (`>`), the left (`<`)
+//!   or the center (`^`), and `{field:.20}` cuts it to at most 20 characters.
+//! - `{?field}...{/}` is only shown if the field is set, that is not empty and not false,
+//!   and `{!field}...{/}` only if it is not.
+//! - `{#red}...{/}` shows the text in a color or a style, like `bold` or `bright blue`,
+//!   several can be joined with `+`, like `{#red+bold}`.
+//!   `{status}` has the colors of the theme.
+//! - `{{` and `}}` are literal braces.
+//!
+//! # Example
+//!
+//! `{id:>3} {status} {#bold}{name}{/}{?description} - {description}{/}`
+
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+use crate::libs::render::{theme, Entry, Renderer};
+use crate::libs::tasks::task::Task;
+
+/// The fields of a task that can be used in a template.
+pub const FIELDS: [&str; 6] = [
+    "id",
+    "name",
+    "description",
+    "status",
+    "completed",
+    "completed_at",
+];
+
+/// The layout of a single task, used by its `Display` implementation.
+pub const TASK_LAYOUT: &str =
+    "Task Name   : {name}\nDescription : {description}\nCompleted   : {status}\n";
+
+/// `task_layout` returns the parsed layout of a single task.
+pub fn task_layout() -> &'static Template {
+    static LAYOUT: OnceLock<Template> = OnceLock::new();
+
+    LAYOUT.get_or_init(|| Template::parse(TASK_LAYOUT).unwrap_or(Template { nodes: vec![] }))
+}
+
+/// `Align` is where a field is put within its width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// `Spec` is how a field is laid out, like `>3` or `.20`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Spec {
+    align: Align,
+    width: usize,
+    max: Option<usize>,
+}
+
+/// `Node` is a piece of a parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Literal text.
+    Text(String),
+    /// A field of the task.
+    Field(&'static str, Spec),
+    /// A section shown if the field is set, or if it is not when negated.
+    Section {
+        field: &'static str,
+        negated: bool,
+        body: Vec<Node>,
+    },
+    /// A section shown with colors and styles.
+    Style { style: String, body: Vec<Node> },
+}
+
+/// `Template` is a parsed template, ready to render tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// `Template` is an implementation of the template.
+impl Template {
+    /// `parse` parses a template.
+    /// Returns a message if a field, a color or a style is unknown,
+    /// or if a section is not closed.
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut stack: Vec<(Node, Vec<Node>)> = vec![];
+        let mut nodes = vec![];
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+
+                '}' => return Err(String::from("unexpected '}', use '}}' for a brace")),
+
+                '{' => {
+                    let mut tag = String::new();
+                    let mut closed = false;
+
+                    for char in chars.by_ref() {
+                        if char == '}' {
+                            closed = true;
+                            break;
+                        }
+
+                        tag.push(char);
+                    }
+
+                    if !closed {
+                        return Err(format!("{{{tag} is not closed, end it with '}}'"));
+                    }
+
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+
+                    match tag.chars().next() {
+                        Some('/') => {
+                            let Some((mut open, outer)) = stack.pop() else {
+                                return Err(String::from("'{/}' without an open section"));
+                            };
+
+                            let body = std::mem::replace(&mut nodes, outer);
+
+                            match &mut open {
+                                Node::Section { body: slot, .. }
+                                | Node::Style { body: slot, .. } => *slot = body,
+                                _ => {}
+                            }
+
+                            nodes.push(open);
+                        }
+
+                        Some(kind @ ('?' | '!')) => {
+                            let open = Node::Section {
+                                field: field(&tag[1..])?,
+                                negated: kind == '!',
+                                body: vec![],
+                            };
+
+                            stack.push((open, std::mem::take(&mut nodes)));
+                        }
+
+                        Some('#') => {
+                            let style = tag[1..].to_string();
+                            theme::parse_style(&style)?;
+
+                            let open = Node::Style {
+                                style,
+                                body: vec![],
+                            };
+
+                            stack.push((open, std::mem::take(&mut nodes)));
+                        }
+
+                        _ => {
+                            let (name, spec) = tag.split_once(':').unwrap_or((&tag, ""));
+                            nodes.push(Node::Field(field(name)?, parse_spec(spec)?));
+                        }
+                    }
+                }
+
+                char => text.push(char),
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(String::from("a section is not closed, end it with '{/}'"));
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        Ok(Template { nodes })
+    }
+
+    /// `render_task` renders the template for a task with the given number.
+    pub fn render_task(&self, number: usize, task: &Task) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, number, task, &mut output);
+        output
+    }
+}
+
+/// `Renderer` is an implementation of the template view, one rendered template per task.
+impl Renderer for Template {
+    fn render(&self, entries: &[Entry]) -> String {
+        entries
+            .iter()
+            .map(|(number, task)| self.render_task(*number, task))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `render_nodes` appends the rendered nodes for a task to the output.
+fn render_nodes(nodes: &[Node], number: usize, task: &Task, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+
+            Node::Field(name, spec) => {
+                let text = layout(&value(name, number, task), spec);
+
+                if *name == "status" {
+                    let style = if task.get_is_completed() {
+                        &theme::theme().completed
+                    } else {
+                        &theme::theme().pending
+                    };
+
+                    let _ = write!(output, "{}", theme::paint(&text, style));
+                } else {
+                    output.push_str(&text);
+                }
+            }
+
+            Node::Section {
+                field,
+                negated,
+                body,
+            } => {
+                if is_set(field, task) != *negated {
+                    render_nodes(body, number, task, output);
+                }
+            }
+
+            Node::Style { style, body } => {
+                let mut inner = String::new();
+                render_nodes(body, number, task, &mut inner);
+                let _ = write!(output, "{}", theme::paint(&inner, style));
+            }
+        }
+    }
+}
+
+/// `field` returns the known field with the given name.
+fn field(name: &str) -> Result<&'static str, String> {
+    let name = name.trim();
+
+    FIELDS
+        .into_iter()
+        .find(|field| *field == name)
+        .ok_or_else(|| {
+            format!(
+                "unknown field {name:?}, expected one of {}",
+                FIELDS.join(", ")
+            )
+        })
+}
+
+/// `value` returns the text of a field of the task.
+fn value(field: &str, number: usize, task: &Task) -> String {
+    match field {
+        "id" => number.to_string(),
+        "name" => task.name.clone(),
+        "description" => task.description.clone(),
+        "status" => task.completed_symbol(),
+        "completed" => task.get_is_completed().to_string(),
+        "completed_at" => task
+            .get_completed_at()
+            .map(|at| at.to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// `is_set` checks if a field of the task is set, that is not empty and not false.
+fn is_set(field: &str, task: &Task) -> bool {
+    match field {
+        "name" => !task.name.is_empty(),
+        "description" => !task.description.is_empty(),
+        "status" | "completed" => task.get_is_completed(),
+        "completed_at" => task.get_completed_at().is_some(),
+        _ => true,
+    }
+}
+
+/// `parse_spec` parses the layout of a field, like `>3`, `<10.20` or `.20`.
+fn parse_spec(spec: &str) -> Result<Spec, String> {
+    let (align, rest) = match spec.chars().next() {
+        Some('<') => (Align::Left, &spec[1..]),
+        Some('>') => (Align::Right, &spec[1..]),
+        Some('^') => (Align::Center, &spec[1..]),
+        _ => (Align::Left, spec),
+    };
+
+    let (width, max) = rest.split_once('.').unwrap_or((rest, ""));
+    let number = |text: &str| {
+        text.parse::<usize>()
+            .map_err(|_| format!("invalid layout {spec:?}, expected something like >3 or .20"))
+    };
+
+    let max = match max {
+        "" => None,
+        max => match number(max)? {
+            0 => {
+                return Err(format!(
+                    "invalid layout {spec:?}, cut to at least 1 character"
+                ))
+            }
+            max => Some(max),
+        },
+    };
+
+    Ok(Spec {
+        align,
+        width: if width.is_empty() { 0 } else { number(width)? },
+        max,
+    })
+}
+
+/// `layout` cuts and aligns a value as the spec says.
+fn layout(value: &str, spec: &Spec) -> String {
+    let value: String = match spec.max {
+        Some(max) if value.chars().count() > max => {
+            let cut: String = value.chars().take(max.saturating_sub(1)).collect();
+            format!("{cut}…")
+        }
+        _ => value.to_string(),
+    };
+
+    let width = spec.width;
+
+    match spec.align {
+        Align::Left => format!("{value:<width$}"),
+        Align::Right => format!("{value:>width$}"),
+        Align::Center => format!("{value:^width$}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `render` parses a template and renders it for a task with the given number.
+    fn render(template: &str, number: usize, task: &Task) -> String {
+        Template::parse(template).unwrap().render_task(number, task)
+    }
+
+    /// `task` returns a pending task with the given name and description.
+    fn task(name: &str, description: &str) -> Task {
+        Task::new(name.to_string(), description.to_string())
+    }
+
+    /// `error` returns the message of a template that does not parse.
+    fn error(template: &str) -> String {
+        Template::parse(template).unwrap_err()
+    }
+
+    #[test]
+    fn fields_and_text() {
+        let task = task("Write", "the docs");
+
+        assert_eq!(
+            render("{id}. {name}: {description} {completed}", 4, &task),
+            "4. Write: the docs false"
+        );
+        assert_eq!(render("{ name }", 0, &task), "Write");
+        assert_eq!(render("{completed_at}", 0, &task), "");
+    }
+
+    #[test]
+    fn completed_at_is_a_field() {
+        let mut task = task("Write", "");
+        task.mark_completed(true, Some(1_700_000_000));
+
+        assert_eq!(render("{completed_at}", 0, &task), "1700000000");
+        assert_eq!(render("{?completed_at}done{/}", 0, &task), "done");
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(render("{{{name}}}", 0, &task("x", "")), "{x}");
+        assert_eq!(render("}}{{", 0, &task("x", "")), "}{");
+    }
+
+    #[test]
+    fn layouts() {
+        let task = task("abcdef", "");
+
+        assert_eq!(render("[{name:>8}]", 0, &task), "[  abcdef]");
+        assert_eq!(render("[{name:<8}]", 0, &task), "[abcdef  ]");
+        assert_eq!(render("[{name:^8}]", 0, &task), "[ abcdef ]");
+        assert_eq!(render("[{name:.4}]", 0, &task), "[abc…]");
+        assert_eq!(render("[{name:>5.4}]", 0, &task), "[ abc…]");
+        assert_eq!(render("[{name:.1}]", 0, &task), "[…]");
+        assert_eq!(render("[{name:.6}]", 0, &task), "[abcdef]");
+    }
+
+    #[test]
+    fn nested_sections() {
+        let template = "{name}{?description} - {description}{!completed} (todo){/}{/}";
+
+        assert_eq!(render(template, 0, &task("a", "b")), "a - b (todo)");
+        assert_eq!(render(template, 0, &task("a", "")), "a");
+        assert_eq!(
+            Template::parse("{?name}{#bold}{name}{/}{/}")
+                .unwrap()
+                .nodes
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(error("{?name}open").contains("not closed"));
+        assert!(error("{?name}{#bold}open{/}").contains("not closed"));
+        assert!(error("{name").contains("not closed"));
+        assert!(error("name}").contains("unexpected '}'"));
+        assert!(error("{/}").contains("without an open section"));
+        assert!(error("{nope}").contains("unknown field"));
+        assert!(error("{?nope}{/}").contains("unknown field"));
+        assert!(error("{name:.0}").contains("at least 1"));
+        assert!(error("{name:>x}").contains("invalid layout"));
+        assert!(error("{#nocolor}{/}").contains("nocolor"));
+    }
+}
@@ -0,0 +1,212 @@
+//! A module to handle the colors of the output.
+//! Every colored part of the output has a role in the `Theme`,
+//! and each role is a style like `green` or `bright red+bold`.
+//! The theme of this run is set once from the configuration.
+//! Tasks have no priority, due date or tags, so there are no roles for them.
+
+use std::sync::OnceLock;
+
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+
+use crate::libs::tasks::task::Task;
+
+/// The styles that are not colors.
+const STYLES: [&str; 4] = ["bold", "dimmed", "italic", "underline"];
+
+/// `Theme` is the style of every colored part of the output.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Theme {
+    /// The `TODO` banner.
+    pub banner: String,
+    /// The header row of the table.
+    pub header: String,
+    /// The status of a completed task.
+    pub completed: String,
+    /// The status of a pending task.
+    pub pending: String,
+    /// The marker of the default list.
+    pub highlight: String,
+    /// The label of an error.
+    pub error: String,
+    /// The label of a warning.
+    pub warning: String,
+    /// The label of an informative message.
+    pub info: String,
+    /// The label of a hint.
+    pub hint: String,
+}
+
+/// `Default` is an implementation of the default theme.
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            banner: String::from("green"),
+            header: String::from("bold"),
+            completed: String::from("green"),
+            pending: String::from("red"),
+            highlight: String::from("green"),
+            error: String::from("red"),
+            warning: String::from("yellow"),
+            info: String::from("yellow"),
+            hint: String::from("cyan"),
+        }
+    }
+}
+
+/// `Theme` is an implementation of the theme.
+impl Theme {
+    /// `roles` returns every role of the theme with its style.
+    pub fn roles(&self) -> [(&'static str, &str); 9] {
+        [
+            ("banner", &self.banner),
+            ("header", &self.header),
+            ("completed", &self.completed),
+            ("pending", &self.pending),
+            ("highlight", &self.highlight),
+            ("error", &self.error),
+            ("warning", &self.warning),
+            ("info", &self.info),
+            ("hint", &self.hint),
+        ]
+    }
+}
+
+/// The theme of this run.
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// `set_theme` sets the theme for this run, only the first call counts.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// `theme` returns the theme of this run, the default one if none was set.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// `parse_style` checks a style, made of colors and styles joined with `+`,
+/// like `red`, `bright_blue` or `yellow+bold`. An empty style shows the text as it is.
+/// Returns the normalized parts, or a message if one is unknown.
+pub fn parse_style(style: &str) -> Result<Vec<String>, String> {
+    style
+        .split('+')
+        .map(|part| part.trim().to_lowercase().replace('_', " "))
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            if STYLES.contains(&part.as_str()) || part.parse::<Color>().is_ok() {
+                Ok(part)
+            } else {
+                Err(format!(
+                    "unknown color or style {part:?}, expected a color like red or bright blue, or {}",
+                    STYLES.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+/// `paint` shows a text with a style, unknown parts of the style are ignored.
+pub fn paint(text: &str, style: &str) -> ColoredString {
+    let mut painted = text.normal();
+
+    for part in style.split('+') {
+        let part = part.trim().to_lowercase().replace('_', " ");
+
+        painted = match part.as_str() {
+            "bold" => painted.bold(),
+            "dimmed" => painted.dimmed(),
+            "italic" => painted.italic(),
+            "underline" => painted.underline(),
+            color => match color.parse::<Color>() {
+                Ok(color) => painted.color(color),
+                Err(_) => painted,
+            },
+        };
+    }
+
+    painted
+}
+
+/// `status` returns the status symbol of a task with the style of the theme.
+pub fn status(task: &Task) -> ColoredString {
+    let style = if task.get_is_completed() {
+        &theme().completed
+    } else {
+        &theme().pending
+    };
+
+    paint(&task.completed_symbol(), style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::Styles;
+    use serde_any::Format;
+
+    #[test]
+    fn missing_roles_fall_back_to_the_default() {
+        let theme: Theme =
+            serde_any::from_str("completed = \"blue\"\nerror = \"\"\n", Format::Toml).unwrap();
+
+        assert_eq!(theme.completed, "blue");
+        assert_eq!(theme.error, "");
+        assert_eq!(
+            Theme {
+                completed: String::from("green"),
+                error: String::from("red"),
+                ..theme
+            },
+            Theme::default()
+        );
+
+        let empty: Theme = serde_any::from_str("", Format::Toml).unwrap();
+        assert_eq!(empty, Theme::default());
+    }
+
+    #[test]
+    fn roles_are_the_fields() {
+        let theme = Theme::default();
+        let fields = serde_json::to_value(&theme).unwrap();
+        let roles = theme.roles();
+
+        assert_eq!(fields.as_object().unwrap().len(), roles.len());
+
+        for (role, style) in roles {
+            assert_eq!(fields[role], style, "theme.{role}");
+        }
+    }
+
+    #[test]
+    fn styles() {
+        assert_eq!(parse_style("red"), Ok(vec![String::from("red")]));
+        assert_eq!(
+            parse_style(" Bright_Blue + bold "),
+            Ok(vec![String::from("bright blue"), String::from("bold")])
+        );
+        assert_eq!(parse_style(""), Ok(vec![]));
+        assert!(parse_style("blurple").is_err());
+        assert!(parse_style("red+blink").is_err());
+
+        for (_, style) in Theme::default().roles() {
+            assert!(parse_style(style).is_ok(), "{style}");
+        }
+    }
+
+    #[test]
+    fn painting() {
+        let painted = paint("text", "bright_blue+bold");
+        assert_eq!(painted.fgcolor(), Some(Color::BrightBlue));
+        assert!(painted.style().contains(Styles::Bold));
+
+        let painted = paint("text", "nope+underline");
+        assert_eq!(painted.fgcolor(), None);
+        assert!(painted.style().contains(Styles::Underline));
+
+        let painted = paint("text", "");
+        assert_eq!(painted.fgcolor(), None);
+        assert!(painted.to_string().contains("text"));
+    }
+}
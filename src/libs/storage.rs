@@ -1,16 +1,23 @@
-use colored::Colorize;
-use serde::{Deserialize, Serialize};
+//! A module to handle the storage of tasks
+//! in the default config format (TOML).
+//! It contains functions to load and save tasks.
+//! It also contains functions to import and export tasks
+//! from and to other formats.
+
+use crate::libs::output::{self, code};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_any::Format;
+use serde_json::{Map, Value};
 use std::fs::File;
+use std::io;
 use std::io::{ErrorKind, Read, Write};
 use std::path::PathBuf;
-use std::{io, process};
 
-/// A module to handle the storage of tasks
-/// in the default config format (TOML).
-/// It contains functions to load and save tasks.
-/// It also contains functions to import and export tasks
-/// from and to other formats.
+pub mod event_log;
+pub mod lock;
+pub mod migration;
+pub mod store;
 
 /// `create` creates a file in the given path.
 /// If the file already exists, it will be overwritten.
@@ -27,18 +34,19 @@ use std::{io, process};
 /// let file = create(&path);
 /// ```
 pub fn create(path: &PathBuf) -> io::Result<File> {
-    match File::create(&path) {
+    match File::create(path) {
         Ok(fs) => Ok(fs),
         Err(error) => match error.kind() {
             ErrorKind::PermissionDenied => {
-                eprintln!(
-                    "{} : Could not create {:?} file, permission denied.",
-                    "ERROR".red(),
-                    &path.to_str().unwrap_or("{unknown route}")
+                output::fail(
+                    code::IO_ERROR,
+                    format!(
+                        "Could not create {:?} file, permission denied.",
+                        &path.to_str().unwrap_or("{unknown route}")
+                    ),
                 );
-                process::exit(exitcode::IOERR);
             }
-            _ => return Err(error),
+            _ => Err(error),
         },
     }
 }
@@ -47,18 +55,19 @@ pub fn create(path: &PathBuf) -> io::Result<File> {
 /// If the file does not exist, the program will exit.
 /// If the file cannot be opened, the program will exit.
 pub(crate) fn open(path: &PathBuf) -> io::Result<File> {
-    match File::open(&path) {
+    match File::open(path) {
         Ok(fs) => Ok(fs),
         Err(error) => match error.kind() {
             ErrorKind::PermissionDenied => {
-                eprintln!(
-                    "{} : Could not open {:?} file, permission denied.",
-                    "ERROR".red(),
-                    &path.to_str().unwrap_or("{unknown route}")
+                output::fail(
+                    code::IO_ERROR,
+                    format!(
+                        "Could not open {:?} file, permission denied.",
+                        &path.to_str().unwrap_or("{unknown route}")
+                    ),
                 );
-                process::exit(exitcode::IOERR);
             }
-            _ => return Err(error),
+            _ => Err(error),
         },
     }
 }
@@ -105,23 +114,28 @@ pub(crate) fn load_raw_or_create(path: &PathBuf) -> io::Result<String> {
 /// `import` imports a string from a given format to a struct.
 /// If the string cannot be deserialized, the program will exit.
 pub fn import<T: for<'de> Deserialize<'de>>(data: &str, format: Format) -> T {
-    match serde_any::from_str(data, format) {
+    match try_import(data, format) {
         Ok(obj) => obj,
         Err(err) => {
-            eprintln!("{} : Deserializing, {}", "ERROR".red(), err.to_string());
-            process::exit(exitcode::DATAERR);
+            output::fail(code::PARSE_ERROR, err.to_string());
         }
     }
 }
 
+/// `try_import` imports a string from a given format to a struct,
+/// returning an error of kind `InvalidData` if it cannot be deserialized.
+pub fn try_import<T: for<'de> Deserialize<'de>>(data: &str, format: Format) -> io::Result<T> {
+    serde_any::from_str(data, format)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, format!("Deserializing, {err}")))
+}
+
 /// `export` exports a given object to a given format.
 /// If the object cannot be serialized, the program will exit.
 pub fn export<T: Serialize>(data: &T, format: Format) -> String {
     match serde_any::to_string(data, format) {
         Ok(serialized) => serialized,
         Err(err) => {
-            eprintln!("{} : Serializing, {}", "ERROR".red(), err);
-            process::exit(exitcode::DATAERR);
+            output::fail(code::INTERNAL, format!("Serializing, {err}"));
         }
     }
 }
@@ -156,3 +170,39 @@ pub fn export_file<T: Serialize>(data: &T, format: Format, path: &PathBuf) -> io
     let data = export(data, format);
     raw_save(&data, path)
 }
+
+/// `TablesLast` serializes a raw map so it can be written as TOML,
+/// which needs plain values before tables and has no null.
+/// Plain values are written first, then tables, and nulls are skipped.
+pub(crate) struct TablesLast<'a>(pub(crate) &'a Map<String, Value>);
+
+/// `is_table` checks if a value is written as a table, or an array of tables, in TOML.
+fn is_table(value: &Value) -> bool {
+    match value {
+        Value::Object(_) => true,
+        Value::Array(items) => !items.is_empty() && items.iter().all(Value::is_object),
+        _ => false,
+    }
+}
+
+impl Serialize for TablesLast<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        for (key, value) in self.0 {
+            if !value.is_null() && !is_table(value) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+
+        for (key, value) in self.0 {
+            match value {
+                Value::Object(table) => map.serialize_entry(key, &TablesLast(table))?,
+                _ if is_table(value) => map.serialize_entry(key, value)?,
+                _ => {}
+            }
+        }
+
+        map.end()
+    }
+}
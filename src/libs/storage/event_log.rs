@@ -0,0 +1,334 @@
+//! A module to store the tasks as an append-only log of events.
+//! Each mutation is appended as a JSON line to a log kept next to the
+//! data file, and the tasks are rebuilt by replaying the log on top of
+//! the last snapshot. Once the log grows long enough it is compacted
+//! into the snapshot and moved to a history file as an audit trail.
+
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_any::Format;
+
+use crate::libs::storage::store::{FileStore, TaskStore};
+use crate::libs::storage::{export, import, load_raw};
+use crate::libs::tasks::event::Event;
+use crate::libs::tasks::{self, Tasks};
+
+/// `Entry` is a line of the log, an event and when it happened.
+#[derive(Deserialize, Serialize, Debug)]
+struct Entry {
+    /// Seconds since the UNIX epoch.
+    at: u64,
+    #[serde(flatten)]
+    event: Event,
+}
+
+/// `with_suffix` appends a suffix to the file name of a path.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `log_path` returns the path of the event log of a data file.
+///
+/// # Example
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use todo::libs::storage::event_log::log_path;
+///
+/// assert_eq!(log_path(Path::new("task.list")), PathBuf::from("task.list.log"));
+/// ```
+pub fn log_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".log")
+}
+
+/// `history_path` returns the path where compacted events of a data file are kept.
+pub fn history_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".history")
+}
+
+/// `replay` applies every event of the log to the given tasks.
+/// Returns the number of events applied, a missing log has none.
+/// If an event cannot be deserialized, the program will exit.
+pub fn replay(tasks: &mut Tasks, log: &Path) -> io::Result<usize> {
+    let raw = match load_raw(&log.to_path_buf()) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+
+    let mut count = 0;
+
+    for line in raw.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: Entry = import(line, Format::Json);
+        tasks.apply(entry.event);
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// `append` appends the given events at the end of the log.
+pub fn append(log: &Path, events: Vec<Event>) -> io::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let at = tasks::now();
+
+    let mut lines = String::new();
+
+    for event in events {
+        lines.push_str(&export(&Entry { at, event }, Format::Json));
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(log)?;
+    file.write_all(lines.as_bytes())
+}
+
+/// `clear` removes the log once its events are part of the snapshot.
+pub fn clear(log: &Path) -> io::Result<()> {
+    match fs::remove_file(log) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// `archive` copies the events of the log to the end of the history file.
+pub(crate) fn archive(log: &Path, history: &Path) -> io::Result<()> {
+    let raw = match load_raw(&log.to_path_buf()) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(history)?;
+    file.write_all(raw.as_bytes())
+}
+
+/// `EventLogStore` stores every change as an event appended to a log,
+/// compacting the log into the snapshot every `compact_after` events.
+#[derive(Debug, Clone)]
+pub struct EventLogStore {
+    snapshot: FileStore,
+    compact_after: usize,
+}
+
+/// `EventLogStore` is an implementation of the event log store.
+impl EventLogStore {
+    /// `new` creates an event log store for the given data path.
+    pub fn new(path: PathBuf, compact_after: usize) -> EventLogStore {
+        EventLogStore {
+            snapshot: FileStore::new(path),
+            compact_after,
+        }
+    }
+
+    /// `compact` moves the logged events to the history file
+    /// and writes the tasks as the new snapshot, see `FileStore::save`.
+    pub fn compact(&mut self, tasks: &Tasks) -> io::Result<()> {
+        self.snapshot.save(tasks)
+    }
+}
+
+impl TaskStore for EventLogStore {
+    /// `load` loads the snapshot and replays the log on top of it.
+    fn load(&self) -> io::Result<Tasks> {
+        self.snapshot.load()
+    }
+
+    /// `save` replaces the stored tasks, compacting the log.
+    fn save(&mut self, tasks: &Tasks) -> io::Result<()> {
+        self.compact(tasks)
+    }
+
+    /// `update` appends the events of the change to the log
+    /// instead of rewriting the snapshot.
+    fn update(
+        &mut self,
+        change: &mut dyn FnMut(&mut Tasks) -> io::Result<()>,
+    ) -> io::Result<Tasks> {
+        let (mut tasks, logged) = self.snapshot.load_with_log()?;
+
+        change(&mut tasks)?;
+
+        let events = tasks.take_events();
+        let log = log_path(self.snapshot.path());
+
+        let total = logged + events.len();
+
+        append(&log, events)?;
+
+        if total >= self.compact_after {
+            self.compact(&tasks)?;
+        }
+
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Dir` is a temporary directory for the data files of a test, removed once dropped.
+    struct Dir(PathBuf);
+
+    impl Dir {
+        fn new(name: &str) -> Dir {
+            let dir =
+                std::env::temp_dir().join(format!("todo-event-log-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Dir(dir)
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for Dir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// `mutate` runs a sequence of changes touching every kind of event through the store, one update each.
+    fn mutate(store: &mut dyn TaskStore) {
+        let changes: [&dyn Fn(&mut Tasks); 6] = [
+            &|tasks| {
+                for name in ["first", "second", "third", "fourth"] {
+                    tasks.add(name, &None);
+                }
+            },
+            &|tasks| {
+                tasks.toggle_completed(2).unwrap();
+            },
+            &|tasks| {
+                tasks.take(0).unwrap();
+            },
+            &|tasks| tasks.edit(0, Some(String::from("renamed")), None).unwrap(),
+            &|tasks| {
+                tasks.set_completed(2, true).unwrap();
+            },
+            &|tasks| {
+                tasks.take(1).unwrap();
+            },
+        ];
+
+        for change in changes {
+            store
+                .update(&mut |tasks| {
+                    change(tasks);
+                    Ok(())
+                })
+                .unwrap();
+        }
+    }
+
+    /// `summary` returns the name and status of every task, in order.
+    fn summary(tasks: &Tasks) -> Vec<(String, bool)> {
+        tasks
+            .iter()
+            .map(|task| (task.name.clone(), task.get_is_completed()))
+            .collect()
+    }
+
+    /// `lines` returns the number of lines of a file, none if it is missing.
+    fn lines(path: &Path) -> usize {
+        fs::read_to_string(path).map_or(0, |raw| raw.lines().count())
+    }
+
+    #[test]
+    fn replaying_the_log_matches_a_snapshot() {
+        let dir = Dir::new("replay");
+        let mut log_store = EventLogStore::new(dir.path("log.list"), 100);
+        let mut file_store = FileStore::new(dir.path("file.list"));
+
+        mutate(&mut log_store);
+        mutate(&mut file_store);
+
+        let replayed = log_store.load().unwrap();
+
+        assert_eq!(summary(&replayed), summary(&file_store.load().unwrap()));
+        assert_eq!(
+            summary(&replayed),
+            [
+                (String::from("renamed"), false),
+                (String::from("fourth"), true)
+            ]
+        );
+        assert_eq!(lines(&log_path(&dir.path("log.list"))), 9);
+        assert!(!history_path(&dir.path("log.list")).exists());
+    }
+
+    #[test]
+    fn compacting_moves_the_log_to_the_history() {
+        let dir = Dir::new("compact");
+        let path = dir.path("task.list");
+        let mut store = EventLogStore::new(path.clone(), 5);
+
+        mutate(&mut store);
+
+        assert_eq!(lines(&history_path(&path)), 5);
+        assert_eq!(lines(&log_path(&path)), 4);
+
+        let (snapshot, _) = FileStore::new(path.clone()).load_with_log().unwrap();
+        let mut bare: Tasks = import(&load_raw(&path).unwrap(), Format::Toml);
+
+        assert_eq!(bare.len(), 4);
+        assert_eq!(summary(&snapshot).len(), 2);
+
+        replay(&mut bare, &log_path(&path)).unwrap();
+        assert_eq!(summary(&bare), summary(&snapshot));
+    }
+
+    #[test]
+    fn snapshots_move_the_log_to_the_history() {
+        let dir = Dir::new("snapshot");
+        let path = dir.path("task.list");
+        let mut store = EventLogStore::new(path.clone(), 100);
+
+        mutate(&mut store);
+        assert_eq!(lines(&log_path(&path)), 9);
+
+        let mut snapshot = FileStore::new(path.clone());
+        let tasks = snapshot.load().unwrap();
+        snapshot.save(&tasks).unwrap();
+
+        assert_eq!(lines(&history_path(&path)), 9);
+        assert!(!log_path(&path).exists());
+        assert_eq!(summary(&snapshot.load().unwrap()), summary(&tasks));
+    }
+
+    #[test]
+    fn replay_ignores_events_of_missing_tasks() {
+        let dir = Dir::new("missing");
+        let log = dir.path("task.list.log");
+        let mut tasks = Tasks::new();
+        tasks.add("only", &None);
+
+        append(
+            &log,
+            vec![
+                Event::Removed { index: 3 },
+                Event::StatusChanged {
+                    index: 1,
+                    completed: true,
+                    completed_at: None,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(replay(&mut tasks, &log).unwrap(), 2);
+        assert_eq!(summary(&tasks), [(String::from("only"), false)]);
+    }
+}
@@ -0,0 +1,113 @@
+//! A module to handle the locking of the data files.
+//! A lock is an advisory lock of the operating system on a `.lock` file next to the data file,
+//! holding the PID of its owner for reference.
+//! The lock is released when it is dropped, or by the operating system when the process exits,
+//! even if it is killed, so a lock is never left behind.
+//! The `.lock` file itself stays, only the lock on it counts.
+//! A lock already held by this process is shared, so a function can lock a file
+//! its caller may have locked already.
+
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+const ATTEMPTS: u32 = 20;
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The lock files this process holds the lock of.
+static HELD: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// `FileLock` holds the lock of a data file while it is alive.
+/// A lock shared with an outer one has no file and releases nothing.
+#[derive(Debug)]
+pub struct FileLock {
+    file: Option<File>,
+    lock: PathBuf,
+}
+
+/// `held` returns the lock files this process holds the lock of.
+fn held() -> std::sync::MutexGuard<'static, Vec<PathBuf>> {
+    HELD.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// `lock_path` returns the path of the lock file of a data file.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// `FileLock` is an implementation of the lock.
+impl FileLock {
+    /// `acquire` locks the given data file, waiting a couple of seconds
+    /// for another process to release it.
+    /// If it is still locked, an error of kind `WouldBlock` is returned.
+    /// If this process holds the lock already, it is shared.
+    pub fn acquire(path: &Path) -> io::Result<FileLock> {
+        let lock = lock_path(path);
+
+        if held().contains(&lock) {
+            return Ok(FileLock { file: None, lock });
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock)?;
+
+        for attempt in 1..=ATTEMPTS {
+            match file.try_lock() {
+                Ok(()) => {
+                    file.set_len(0)?;
+                    write!(file, "{}", std::process::id())?;
+                    held().push(lock.clone());
+
+                    return Ok(FileLock {
+                        file: Some(file),
+                        lock,
+                    });
+                }
+
+                Err(TryLockError::WouldBlock) => {
+                    if attempt < ATTEMPTS {
+                        thread::sleep(RETRY_DELAY);
+                    }
+                }
+
+                Err(TryLockError::Error(err)) => return Err(err),
+            }
+        }
+
+        Err(io::Error::new(
+            ErrorKind::WouldBlock,
+            format!("{lock:?} is locked by another process"),
+        ))
+    }
+
+    /// `acquire_all` locks all the given data files.
+    /// The files are always locked in the same order to avoid deadlocks,
+    /// and a file given twice is locked once.
+    pub fn acquire_all(paths: &[&Path]) -> io::Result<Vec<FileLock>> {
+        let mut paths: Vec<&Path> = paths.to_vec();
+        paths.sort();
+        paths.dedup();
+
+        paths.into_iter().map(FileLock::acquire).collect()
+    }
+}
+
+/// `Drop` is an implementation to release the lock.
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            held().retain(|held| held != &self.lock);
+            let _ = file.unlock();
+        }
+    }
+}
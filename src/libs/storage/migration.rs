@@ -0,0 +1,325 @@
+//! A module to handle the versioning of the data files.
+//! Every file written by the application carries a `version` field,
+//! files from older versions are upgraded on load and files from
+//! newer versions are refused.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_any::Format;
+use serde_json::{Map, Value};
+
+use crate::libs::output::{self, code};
+use crate::libs::render::theme::{paint, theme};
+use crate::libs::storage::lock::FileLock;
+use crate::libs::storage::{export_file, load_raw, load_raw_or_create, try_import, TablesLast};
+
+/// A migration upgrades the raw representation of a file by one version.
+pub type Migration = fn(&mut Map<String, Value>);
+
+/// `Schema` describes the versions a kind of data file went through.
+/// The migration at position `n` upgrades a file from version `n` to `n + 1`,
+/// so the current version is the number of migrations.
+pub struct Schema {
+    name: &'static str,
+    migrations: &'static [Migration],
+}
+
+/// The schema of the tasks data file.
+pub const TASKS: Schema = Schema {
+    name: "Data file",
+    migrations: &[unversioned],
+};
+
+/// The schema of the trash of a list.
+pub const TRASH: Schema = Schema {
+    name: "Trash",
+    migrations: &[unversioned],
+};
+
+/// The schema of the configuration file.
+pub const CONFIG: Schema = Schema {
+    name: "Config file",
+    migrations: &[unversioned],
+};
+
+/// `unversioned` upgrades files written before versioning existed,
+/// their layout is the same as the first version so only the version is stamped.
+fn unversioned(_: &mut Map<String, Value>) {}
+
+/// `Schema` is an implementation of the schema.
+impl Schema {
+    /// `version` returns the current version of the schema.
+    pub const fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    /// `migrate` upgrades the given raw data from the given version to the current one.
+    pub fn migrate(&self, data: &mut Map<String, Value>, from: u32) {
+        for migration in &self.migrations[from as usize..] {
+            migration(data);
+        }
+
+        data.insert(String::from("version"), Value::from(self.version()));
+    }
+}
+
+/// `version_of` returns the version of the given raw data.
+/// Files without a version are considered version 0.
+fn version_of(data: &Map<String, Value>) -> u32 {
+    data.get("version")
+        .and_then(Value::as_u64)
+        .map(|version| version as u32)
+        .unwrap_or(0)
+}
+
+/// `backup_path` returns the path where the pre-migration copy of a file is kept.
+///
+/// # Example
+///
+/// `task.list` at version 0 is backed up as `task.list.v0.bak`.
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".v{version}.bak"));
+    PathBuf::from(name)
+}
+
+/// `import_file_versioned` imports a file like `import_file_or_create`,
+/// upgrading it to the current version of the schema first.
+///
+/// If the file is upgraded, a backup of the original is kept next to it
+/// and the upgraded file is saved in place.
+/// If the file comes from a newer version, an error of kind `InvalidData` is returned.
+pub fn import_file_versioned<T>(path: &PathBuf, format: Format, schema: &Schema) -> io::Result<T>
+where
+    T: DeserializeOwned + Default,
+{
+    let data = import_file_versioned_raw(path, format, schema)?;
+
+    if data.is_empty() {
+        return Ok(T::default());
+    }
+
+    Ok(deserialize(data, schema))
+}
+
+/// `import_file_versioned_raw` works like `import_file_versioned`,
+/// but returns the raw upgraded data instead of a struct.
+/// An empty file has no data.
+///
+/// The upgrade holds the lock of the file, so no other process writes it meanwhile,
+/// and the file is read again once locked, in case another process upgraded it first.
+pub fn import_file_versioned_raw(
+    path: &PathBuf,
+    format: Format,
+    schema: &Schema,
+) -> io::Result<Map<String, Value>> {
+    let (data, version) = parse(&load_raw_or_create(path)?, path, format, schema)?;
+
+    if version == schema.version() {
+        return Ok(data);
+    }
+
+    let _lock = FileLock::acquire(path)?;
+    let (mut data, version) = parse(&load_raw(path)?, path, format, schema)?;
+
+    if version == schema.version() {
+        return Ok(data);
+    }
+
+    let backup = backup_path(path, version);
+    fs::copy(path, &backup)?;
+
+    schema.migrate(&mut data, version);
+    export_file(&TablesLast(&data), format, path)?;
+
+    eprintln!(
+        "{} : {} upgraded from version {} to {}, backup kept at {:?}.",
+        paint("INFO", &theme().info),
+        schema.name,
+        version,
+        schema.version(),
+        backup
+    );
+
+    Ok(data)
+}
+
+/// `read_file_versioned_raw` reads the raw data of an existing file,
+/// upgrading it to the current version of the schema in memory only.
+/// Used for files the application reads but does not own, like the system config,
+/// and for files rewritten whole on every save, like the trash.
+/// If the file comes from a newer version, an error of kind `InvalidData` is returned.
+pub fn read_file_versioned_raw(
+    path: &PathBuf,
+    format: Format,
+    schema: &Schema,
+) -> io::Result<Map<String, Value>> {
+    let (mut data, version) = parse(&load_raw(path)?, path, format, schema)?;
+
+    if version < schema.version() {
+        schema.migrate(&mut data, version);
+    }
+
+    Ok(data)
+}
+
+/// `parse` returns the raw data of a file and its version.
+/// An empty file has no data and is at the current version, as there is nothing to upgrade.
+fn parse(
+    raw: &str,
+    path: &Path,
+    format: Format,
+    schema: &Schema,
+) -> io::Result<(Map<String, Value>, u32)> {
+    if raw.is_empty() {
+        return Ok((Map::new(), schema.version()));
+    }
+
+    let data: Map<String, Value> = try_import(raw, format)?;
+    let version = checked_version(&data, path, schema)?;
+
+    Ok((data, version))
+}
+
+/// `checked_version` returns the version of the given raw data.
+/// If the data comes from a newer version than the schema, an error of kind `InvalidData` is returned.
+fn checked_version(data: &Map<String, Value>, path: &Path, schema: &Schema) -> io::Result<u32> {
+    let version = version_of(data);
+
+    if version > schema.version() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{:?} is version {}, but this build of todo only supports up to version {}. Please upgrade todo.",
+                path,
+                version,
+                schema.version()
+            ),
+        ));
+    }
+
+    Ok(version)
+}
+
+/// `deserialize` converts the raw data into the final struct.
+/// If the data does not match the struct, the program will exit.
+fn deserialize<T: DeserializeOwned>(data: Map<String, Value>, schema: &Schema) -> T {
+    match serde_json::from_value(Value::Object(data)) {
+        Ok(obj) => obj,
+        Err(err) => output::fail(code::PARSE_ERROR, format!("{}, {}", schema.name, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Dir` is a temporary directory for the files of a test, removed once dropped.
+    struct Dir(PathBuf);
+
+    impl Dir {
+        fn new(name: &str) -> Dir {
+            let dir =
+                std::env::temp_dir().join(format!("todo-migration-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Dir(dir)
+        }
+
+        fn write(&self, name: &str, content: &str) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Dir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    const UNVERSIONED: &str = "[[tasks]]\nname = \"Task\"\ndescription = \"\"\n";
+
+    #[test]
+    fn unversioned_files_are_stamped() {
+        let dir = Dir::new("stamp");
+        let path = dir.write("task.list", UNVERSIONED);
+
+        let data = import_file_versioned_raw(&path, Format::Toml, &TASKS).unwrap();
+
+        assert_eq!(version_of(&data), TASKS.version());
+        assert_eq!(data["tasks"][0]["name"], "Task");
+
+        let upgraded: Map<String, Value> =
+            try_import(&fs::read_to_string(&path).unwrap(), Format::Toml).unwrap();
+        assert_eq!(version_of(&upgraded), 1);
+        assert_eq!(upgraded["tasks"], data["tasks"]);
+    }
+
+    #[test]
+    fn upgrades_keep_a_backup() {
+        let dir = Dir::new("backup");
+        let path = dir.write("task.list", UNVERSIONED);
+
+        import_file_versioned_raw(&path, Format::Toml, &TASKS).unwrap();
+
+        let backup = backup_path(&path, 0);
+        assert_eq!(backup, dir.0.join("task.list.v0.bak"));
+        assert_eq!(fs::read_to_string(backup).unwrap(), UNVERSIONED);
+    }
+
+    #[test]
+    fn current_files_are_left_alone() {
+        let dir = Dir::new("current");
+        let content = format!("version = 1\n{UNVERSIONED}");
+        let path = dir.write("task.list", &content);
+
+        import_file_versioned_raw(&path, Format::Toml, &TASKS).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert!(!backup_path(&path, 1).exists());
+    }
+
+    #[test]
+    fn newer_files_are_refused() {
+        let dir = Dir::new("newer");
+        let content = format!("version = 2\n{UNVERSIONED}");
+        let path = dir.write("task.list", &content);
+
+        let err = import_file_versioned_raw(&path, Format::Toml, &TASKS).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = read_file_versioned_raw(&path, Format::Toml, &TRASH).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert!(!backup_path(&path, 2).exists());
+    }
+
+    #[test]
+    fn upgrades_share_the_lock_of_the_caller() {
+        let dir = Dir::new("locked");
+        let path = dir.write("task.list", UNVERSIONED);
+        let _lock = FileLock::acquire(&path).unwrap();
+
+        let data = import_file_versioned_raw(&path, Format::Toml, &TASKS).unwrap();
+
+        assert_eq!(version_of(&data), 1);
+    }
+
+    #[test]
+    fn reads_upgrade_in_memory_only() {
+        let dir = Dir::new("read");
+        let path = dir.write("task.list.trash", UNVERSIONED);
+
+        let data = read_file_versioned_raw(&path, Format::Toml, &TRASH).unwrap();
+
+        assert_eq!(version_of(&data), TRASH.version());
+        assert_eq!(fs::read_to_string(&path).unwrap(), UNVERSIONED);
+    }
+}
@@ -0,0 +1,260 @@
+//! A module to abstract where the tasks are persisted.
+//! It contains the `TaskStore` trait and the stores shipped with the library.
+
+use std::io;
+use std::path::PathBuf;
+
+use serde_any::Format;
+
+use crate::libs::storage::event_log::{self, history_path, log_path};
+use crate::libs::storage::export_file;
+use crate::libs::storage::migration::{self, import_file_versioned};
+use crate::libs::tasks::task::Task;
+use crate::libs::tasks::Tasks;
+
+/// `TaskStore` is the interface every persistence backend implements.
+///
+/// Only `load` and `save` are required, the rest of the operations
+/// are built on top of them and can be overridden when a backend
+/// has a cheaper way to do them.
+///
+/// # Example
+///
+/// ```
+/// use todo::libs::storage::store::{MemoryStore, TaskStore};
+///
+/// let mut store = MemoryStore::new();
+///
+/// store
+///     .update(&mut |tasks| {
+///         tasks.add("Write docs", &None);
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// assert_eq!(store.list().unwrap().len(), 1);
+/// ```
+pub trait TaskStore {
+    /// `load` loads all the tasks from the store.
+    fn load(&self) -> io::Result<Tasks>;
+
+    /// `save` replaces the stored tasks with the given ones.
+    fn save(&mut self, tasks: &Tasks) -> io::Result<()>;
+
+    /// `update` loads the tasks, applies the given change and saves the result.
+    /// If the change fails nothing is saved and the error is returned.
+    fn update(
+        &mut self,
+        change: &mut dyn FnMut(&mut Tasks) -> io::Result<()>,
+    ) -> io::Result<Tasks> {
+        let mut tasks = self.load()?;
+
+        change(&mut tasks)?;
+        self.save(&tasks)?;
+
+        Ok(tasks)
+    }
+
+    /// `list` returns all the stored tasks in order.
+    fn list(&self) -> io::Result<Vec<Task>> {
+        Ok(self.load()?.iter().cloned().collect())
+    }
+
+    /// `get` returns the task with the given number, if it exists.
+    fn get(&self, index: usize) -> io::Result<Option<Task>> {
+        Ok(self.load()?.iter().nth(index).cloned())
+    }
+}
+
+/// `FileStore` stores the tasks as a snapshot in a single file.
+/// This is the storage used by the command line by default.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    format: Format,
+}
+
+/// `FileStore` is an implementation of the file store.
+impl FileStore {
+    /// `new` creates a store for the given path in the default format (TOML).
+    pub fn new(path: PathBuf) -> FileStore {
+        FileStore::with_format(path, Format::Toml)
+    }
+
+    /// `with_format` creates a store for the given path in the given format.
+    pub fn with_format(path: PathBuf, format: Format) -> FileStore {
+        FileStore { path, format }
+    }
+
+    /// `path` returns the path of the file backing the store.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// `load_with_log` loads the snapshot and replays the pending event log on top of it.
+    /// Returns the tasks and the number of events replayed.
+    pub(crate) fn load_with_log(&self) -> io::Result<(Tasks, usize)> {
+        let mut tasks: Tasks = import_file_versioned(&self.path, self.format, &migration::TASKS)?;
+        let replayed = event_log::replay(&mut tasks, &log_path(&self.path))?;
+
+        Ok((tasks, replayed))
+    }
+}
+
+impl TaskStore for FileStore {
+    /// `load` loads the tasks from the file, creating it if it does not exist.
+    /// Files from older versions are upgraded on load,
+    /// and events left in the log by an `EventLogStore` are replayed.
+    fn load(&self) -> io::Result<Tasks> {
+        Ok(self.load_with_log()?.0)
+    }
+
+    /// `save` overwrites the file with the given tasks,
+    /// moving the events left in the log to the history as they are now part of the snapshot.
+    fn save(&mut self, tasks: &Tasks) -> io::Result<()> {
+        let log = log_path(&self.path);
+
+        event_log::archive(&log, &history_path(&self.path))?;
+        export_file(tasks, self.format, &self.path)?;
+        event_log::clear(&log)
+    }
+}
+
+/// `MemoryStore` keeps the tasks in memory.
+/// Useful for tests and for consumers that handle persistence by themselves.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    tasks: Tasks,
+}
+
+/// `MemoryStore` is an implementation of the in-memory store.
+impl MemoryStore {
+    /// `new` creates an empty in-memory store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// `with_tasks` creates an in-memory store holding the given tasks.
+    pub fn with_tasks(tasks: Tasks) -> MemoryStore {
+        MemoryStore { tasks }
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn load(&self) -> io::Result<Tasks> {
+        Ok(self.tasks.clone())
+    }
+
+    fn save(&mut self, tasks: &Tasks) -> io::Result<()> {
+        self.tasks = tasks.clone();
+        Ok(())
+    }
+
+    fn list(&self) -> io::Result<Vec<Task>> {
+        Ok(self.tasks.iter().cloned().collect())
+    }
+
+    fn get(&self, index: usize) -> io::Result<Option<Task>> {
+        Ok(self.tasks.iter().nth(index).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Plain` is a store with only `load` and `save`, to check the default operations.
+    #[derive(Default)]
+    struct Plain {
+        tasks: Tasks,
+        saves: usize,
+    }
+
+    impl TaskStore for Plain {
+        fn load(&self) -> io::Result<Tasks> {
+            Ok(self.tasks.clone())
+        }
+
+        fn save(&mut self, tasks: &Tasks) -> io::Result<()> {
+            self.tasks = tasks.clone();
+            self.saves += 1;
+            Ok(())
+        }
+    }
+
+    /// `add` adds tasks with the given names through `update`.
+    fn add(store: &mut dyn TaskStore, names: &[&str]) -> io::Result<Tasks> {
+        store.update(&mut |tasks| {
+            for name in names {
+                tasks.add(name, &None);
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn update_saves_the_change() {
+        let mut store = Plain::default();
+
+        let tasks = add(&mut store, &["first", "second"]).unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(store.saves, 1);
+        assert_eq!(store.load().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn update_does_not_save_a_failed_change() {
+        let mut store = Plain::default();
+        add(&mut store, &["first"]).unwrap();
+
+        let result = store.update(&mut |tasks| {
+            tasks.add("lost", &None);
+            Err(io::Error::other("refused"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(store.saves, 1);
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn default_list_and_get_follow_the_order() {
+        let mut store = Plain::default();
+        add(&mut store, &["first", "second"]).unwrap();
+
+        let names: Vec<String> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|task| task.name)
+            .collect();
+
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(store.get(1).unwrap().unwrap().name, "second");
+        assert!(store.get(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn memory_store_keeps_the_tasks() {
+        let mut store = MemoryStore::new();
+        add(&mut store, &["first", "second"]).unwrap();
+
+        let result = store.update(&mut |tasks| {
+            tasks.clear();
+            Err(io::Error::other("refused"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(store.list().unwrap().len(), 2);
+        assert_eq!(store.get(0).unwrap().unwrap().name, "first");
+        assert!(store.get(2).unwrap().is_none());
+        assert_eq!(
+            MemoryStore::with_tasks(store.load().unwrap())
+                .list()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
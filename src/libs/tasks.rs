@@ -1,28 +1,147 @@
-use std::process;
-
+use crate::libs::storage::migration;
+use crate::libs::tasks::event::Event;
+use crate::libs::tasks::order::Place;
 use crate::libs::tasks::task::Task;
-use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod archive;
+pub mod event;
 pub mod io;
+pub mod order;
+pub mod selection;
 pub mod task;
+pub mod trash;
 
 /// A module to handle the tasks of the application.
 /// It contains functions to add, modify, remove, toggle and list tasks.
-#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Tasks {
+    #[serde(default = "current_version")]
+    version: u32,
     tasks: Vec<Task>,
+    #[serde(skip)]
+    events: Vec<Event>,
+    #[serde(skip)]
+    trashed: Vec<Task>,
+}
+
+/// `current_version` returns the version of the data file written by this build.
+fn current_version() -> u32 {
+    migration::TASKS.version()
+}
+
+/// The seconds in a day, to turn the days of the configuration into a time.
+pub const DAY: u64 = 24 * 60 * 60;
+
+/// `now` returns the current time in seconds since the UNIX epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// `NotFound` is the error of an operation on a task number that does not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotFound(pub usize);
+
+/// `Display` is an implementation to describe the missing task.
+impl Display for NotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Task {} not found.", self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// `From` is an implementation to fail a change of a store with a missing task.
+impl From<NotFound> for std::io::Error {
+    fn from(err: NotFound) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::NotFound, err)
+    }
+}
+
+/// `Default` is an implementation of the default tasks, an empty list.
+impl Default for Tasks {
+    fn default() -> Self {
+        Tasks::new()
+    }
 }
 
 /// `Tasks` is an implementation of the tasks.
 impl Tasks {
     /// `new` creates a new instance of `Tasks`.
     pub fn new() -> Tasks {
-        Tasks { tasks: vec![] }
+        Tasks {
+            version: current_version(),
+            tasks: vec![],
+            events: vec![],
+            trashed: vec![],
+        }
     }
 
-    /// `add` adds a new task to the tasks.
-    pub fn add(&mut self, name: &str, description: &Option<String>) {
+    /// `apply` applies an event to the tasks without recording it.
+    /// Used to rebuild the tasks by replaying an event log.
+    /// Events pointing to tasks that do not exist are ignored.
+    pub fn apply(&mut self, event: Event) {
+        match event {
+            Event::Added { task } => self.tasks.push(task),
+
+            Event::Edited {
+                index,
+                name,
+                description,
+            } => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.name = name;
+                    task.description = description;
+                }
+            }
+
+            Event::StatusChanged {
+                index,
+                completed,
+                completed_at,
+            } => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.mark_completed(completed, completed_at);
+                }
+            }
+
+            Event::Removed { index } => {
+                if index < self.tasks.len() {
+                    self.tasks.remove(index);
+                }
+            }
+
+            Event::Moved { from, to } => {
+                if from < self.tasks.len() && to < self.tasks.len() {
+                    let task = self.tasks.remove(from);
+                    self.tasks.insert(to, task);
+                }
+            }
+
+            Event::Cleared => self.tasks.clear(),
+        }
+    }
+
+    /// `record` applies an event to the tasks and keeps it
+    /// until it is collected with `take_events`.
+    fn record(&mut self, event: Event) {
+        self.events.push(event.clone());
+        self.apply(event);
+    }
+
+    /// `take_events` returns the events recorded since the tasks were loaded
+    /// or since the last call, leaving none recorded.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// `add` adds a new task to the tasks and returns its number.
+    pub fn add(&mut self, name: &str, description: &Option<String>) -> usize {
         let description = match description {
             Some(desc) => desc,
             None => "",
@@ -30,41 +149,151 @@ impl Tasks {
 
         let task = Task::new(name.to_string(), description.to_string());
 
-        println!("{}", &task);
+        self.record(Event::Added { task });
 
-        self.tasks.push(task);
+        self.tasks.len() - 1
     }
 
-    /// `remove` removes a task from the tasks.
-    pub fn remove(&mut self, index: usize) {
-        let tasks = self.get(index);
+    /// `edit` changes the name and description of a task.
+    /// Fields left as None are kept as they are.
+    pub fn edit(
+        &mut self,
+        index: usize,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> Result<(), NotFound> {
+        let task = self.find(index)?;
+
+        let name = name.unwrap_or_else(|| task.name.clone());
+        let description = description.unwrap_or_else(|| task.description.clone());
 
-        println!("{index} - {}", tasks.to_string_short());
-        self.tasks.remove(index);
+        self.record(Event::Edited {
+            index,
+            name,
+            description,
+        });
+
+        Ok(())
     }
 
-    /// `toggle_completed` toggles the completed status of a task.
-    pub fn toggle_completed(&self, index: usize) {
-        let tasks = self.get(index);
+    /// `push` appends an existing task, keeping all its fields.
+    pub fn push(&mut self, task: Task) {
+        self.record(Event::Added { task });
+    }
 
-        tasks.toggle_completed();
+    /// `take` removes a task from the tasks and returns it.
+    pub fn take(&mut self, index: usize) -> Result<Task, NotFound> {
+        let task = self.find(index)?.clone();
 
-        println!("{index} - {}", tasks.to_string_short());
+        self.record(Event::Removed { index });
+
+        Ok(task)
+    }
+
+    /// `toggle_completed` toggles the completed status of a task and returns the new one.
+    pub fn toggle_completed(&mut self, index: usize) -> Result<bool, NotFound> {
+        let completed = !self.find(index)?.get_is_completed();
+
+        self.record(Event::StatusChanged {
+            index,
+            completed,
+            completed_at: completed.then(now),
+        });
+
+        Ok(completed)
+    }
+
+    /// `take_many` removes the tasks with the given numbers, in order and each one once,
+    /// and returns them with their numbers from before the removal.
+    /// When all the tasks are taken, they are cleared at once.
+    /// If a number is missing, nothing is removed.
+    pub fn take_many(&mut self, indexes: &[usize]) -> Result<Vec<(usize, Task)>, NotFound> {
+        if let Some(&missing) = indexes.iter().find(|&&index| index >= self.len()) {
+            return Err(NotFound(missing));
+        }
+
+        if !indexes.is_empty() && indexes.len() == self.len() {
+            let taken = self.tasks.iter().cloned().enumerate().collect();
+            self.clear();
+            return Ok(taken);
+        }
+
+        let mut taken = vec![];
+
+        for &index in indexes.iter().rev() {
+            taken.push((index, self.take(index)?));
+        }
+
+        taken.reverse();
+        Ok(taken)
+    }
+
+    /// `remove` removes a task and returns it, keeping it to be put in the trash when saved.
+    pub fn remove(&mut self, index: usize) -> Result<Task, NotFound> {
+        let task = self.take(index)?;
+        self.trashed.push(task.clone());
+        Ok(task)
+    }
+
+    /// `remove_many` removes the tasks with the given numbers like `take_many`,
+    /// keeping them to be put in the trash when saved.
+    pub fn remove_many(&mut self, indexes: &[usize]) -> Result<Vec<(usize, Task)>, NotFound> {
+        let removed = self.take_many(indexes)?;
+        self.trashed
+            .extend(removed.iter().map(|(_, task)| task.clone()));
+        Ok(removed)
     }
 
-    /// `list_long` lists the tasks in a long format.
-    pub fn list_long(&self) {
-        for (number, task) in self.tasks.iter().enumerate() {
-            println!("TASK NUMBER : {number}");
-            println!("{}", task.to_string())
+    /// `take_trashed` returns the tasks removed since the tasks were loaded
+    /// or since the last call, to be put in the trash.
+    pub fn take_trashed(&mut self) -> Vec<Task> {
+        std::mem::take(&mut self.trashed)
+    }
+
+    /// `set_completed` sets the completed status of a task.
+    /// Returns false if it already had it, then nothing is recorded.
+    pub fn set_completed(&mut self, index: usize, completed: bool) -> Result<bool, NotFound> {
+        if self.find(index)?.get_is_completed() == completed {
+            return Ok(false);
         }
+
+        self.record(Event::StatusChanged {
+            index,
+            completed,
+            completed_at: completed.then(now),
+        });
+
+        Ok(true)
     }
 
-    /// `list_short` lists the tasks in a short format.
-    pub fn list_short(&self) {
-        for (number, task) in self.tasks.iter().enumerate() {
-            println!("Task : {number} - {}", task.to_string_short());
+    /// `reorder` moves a task to another place in the list and returns its new number.
+    /// Returns None if the place is relative to the task itself, then nothing is recorded.
+    pub fn reorder(&mut self, index: usize, place: Place) -> Result<Option<usize>, NotFound> {
+        self.find(index)?;
+
+        if let Some(anchor) = place.anchor() {
+            self.find(anchor)?;
+        }
+
+        let Some(to) = place.position(index, self.len()) else {
+            return Ok(None);
+        };
+
+        if to != index {
+            self.record(Event::Moved { from: index, to });
         }
+
+        Ok(Some(to))
+    }
+
+    /// `clear` removes all the tasks.
+    pub fn clear(&mut self) {
+        self.record(Event::Cleared);
+    }
+
+    /// `iter` returns an iterator over the tasks in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Task> {
+        self.tasks.iter()
     }
 
     /// `len` returns the length of the tasks.
@@ -77,14 +306,13 @@ impl Tasks {
         self.tasks.is_empty()
     }
 
-    /// Get a Task by Index, exit process if not found.
-    pub fn get(&self, index: usize) -> &Task {
-        match self.tasks.get(index) {
-            Some(s) => s,
-            None => {
-                eprintln!("{} : Not found.", "ERROR".red());
-                process::exit(1);
-            }
-        }
+    /// `get` returns the task with the given number, if it exists.
+    pub fn get(&self, index: usize) -> Option<&Task> {
+        self.tasks.get(index)
+    }
+
+    /// `find` returns the task with the given number, or the error to return if it does not exist.
+    pub fn find(&self, index: usize) -> Result<&Task, NotFound> {
+        self.get(index).ok_or(NotFound(index))
     }
 }
@@ -0,0 +1,137 @@
+//! A module to handle the archive of a list.
+//! The archive is a data file next to the one of the list, named like it with `.archive` appended,
+//! keeping finished tasks out of the way instead of removing them.
+//! It is always written as a snapshot, whatever the storage of the list.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::libs::config::Config;
+use crate::libs::output::{self, code};
+use crate::libs::storage::event_log::with_suffix;
+use crate::libs::storage::store::{FileStore, TaskStore};
+use crate::libs::tasks::task::Task;
+use crate::libs::tasks::{self, NotFound, Tasks, DAY};
+
+/// `archive_path` returns the path of the archive of a data file.
+///
+/// # Example
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use todo::libs::tasks::archive::archive_path;
+///
+/// assert_eq!(archive_path(Path::new("task.list")), PathBuf::from("task.list.archive"));
+/// ```
+pub fn archive_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".archive")
+}
+
+/// `load` loads the archived tasks of the list in use.
+pub fn load(config: &Config) -> Tasks {
+    tasks::io::load(&archive_path(&config.get_data_path()))
+}
+
+/// `archive` moves the tasks `choose` returns the numbers of
+/// from the list in use to the end of its archive.
+/// Returns the moved tasks with their numbers in the list.
+pub fn archive(config: &Config, choose: impl FnOnce(&Tasks) -> Vec<usize>) -> Vec<(usize, Task)> {
+    let path = config.get_data_path();
+    let archive = archive_path(&path);
+    let _locks = tasks::io::lock(&[&path, &archive]);
+
+    let result = shift(
+        tasks::io::store(config, path).as_mut(),
+        &mut FileStore::new(archive),
+        choose,
+    );
+
+    match result {
+        Ok(moved) => moved,
+        Err(err) => output::fail(code::of_io(&err), format!("Archive, {err}")),
+    }
+}
+
+/// `unarchive` moves the archived tasks `choose` returns the numbers of
+/// back to the end of the list in use.
+/// Returns the moved tasks with their numbers in the archive.
+pub fn unarchive(config: &Config, choose: impl FnOnce(&Tasks) -> Vec<usize>) -> Vec<(usize, Task)> {
+    let path = config.get_data_path();
+    let archive = archive_path(&path);
+    let _locks = tasks::io::lock(&[&path, &archive]);
+
+    let result = shift(
+        &mut FileStore::new(archive),
+        tasks::io::store(config, path).as_mut(),
+        choose,
+    );
+
+    match result {
+        Ok(moved) => moved,
+        Err(err) => output::fail(code::of_io(&err), format!("Archive, {err}")),
+    }
+}
+
+/// `auto_archive` archives the tasks of the list in use completed more than
+/// `archive_after_days` days ago, if it is configured.
+/// Tasks completed before the time of completion was recorded are kept.
+/// Returns the archived tasks with their numbers in the list.
+pub fn auto_archive(config: &Config) -> Vec<(usize, Task)> {
+    let Some(days) = config.get_archive_after_days() else {
+        return vec![];
+    };
+
+    let before = tasks::now().saturating_sub(days * DAY);
+    let expired = |tasks: &Tasks| -> Vec<usize> {
+        tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.get_completed_at().is_some_and(|at| at <= before))
+            .map(|(index, _)| index)
+            .collect()
+    };
+
+    let current = tasks::io::load(&config.get_data_path());
+
+    if expired(&current).is_empty() {
+        return vec![];
+    }
+
+    archive(config, expired)
+}
+
+/// `shift` moves the tasks `choose` returns the numbers of from one store to the end of another.
+/// The tasks are added to the target before they are removed from the source,
+/// so a failure midway never loses them.
+fn shift(
+    source: &mut dyn TaskStore,
+    target: &mut dyn TaskStore,
+    choose: impl FnOnce(&Tasks) -> Vec<usize>,
+) -> io::Result<Vec<(usize, Task)>> {
+    let tasks = source.load()?;
+    let indexes = choose(&tasks);
+
+    if indexes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let moved = indexes
+        .iter()
+        .map(|&index| Ok((index, tasks.get(index).ok_or(NotFound(index))?.clone())))
+        .collect::<io::Result<Vec<(usize, Task)>>>()?;
+
+    target.update(&mut |target| {
+        for (_, task) in &moved {
+            target.push(task.clone());
+        }
+
+        Ok(())
+    })?;
+
+    source.update(&mut |source| {
+        source.take_many(&indexes)?;
+        Ok(())
+    })?;
+
+    Ok(moved)
+}
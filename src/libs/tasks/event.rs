@@ -0,0 +1,39 @@
+//! A module to handle the events of the tasks.
+//! Every mutation of `Tasks` is described by an event,
+//! so the list can be rebuilt by replaying them in order.
+
+use serde::{Deserialize, Serialize};
+
+use crate::libs::tasks::task::Task;
+
+/// `Event` is a single mutation applied to the tasks.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A task was appended to the list.
+    Added { task: Task },
+
+    /// The name and description of a task were changed.
+    Edited {
+        index: usize,
+        name: String,
+        description: String,
+    },
+
+    /// A task was marked as completed or pending, and when it was completed.
+    StatusChanged {
+        index: usize,
+        completed: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        completed_at: Option<u64>,
+    },
+
+    /// A task was removed from the list.
+    Removed { index: usize },
+
+    /// A task was moved to another place in the list, shifting the ones in between.
+    Moved { from: usize, to: usize },
+
+    /// All the tasks were removed.
+    Cleared,
+}
@@ -1,30 +1,140 @@
-use std::path::PathBuf;
-use std::{io, process};
+//! A module to handle the tasks of the application.
 
-use crate::libs::storage::{export_file, import_file_or_create};
-use crate::libs::tasks::Tasks;
-use colored::Colorize;
-use serde_any::Format;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
 
-/// A module to handle the tasks of the application.
+use crate::libs::config::{Config, StorageMode};
+use crate::libs::output::{self, code};
+use crate::libs::storage::event_log::{self, EventLogStore};
+use crate::libs::storage::lock::FileLock;
+use crate::libs::storage::store::{FileStore, TaskStore};
+use crate::libs::tasks::{trash, Tasks};
 
-/// `default_data_path` returns the default data path.
-pub fn save(tasks: &Tasks, path: &PathBuf) {
-    if let Err(err) = export_file(tasks, Format::Toml, &path) {
-        eprintln!("{} : Config file, {}", "ERROR".red(), err.to_string());
-        process::exit(exitcode::IOERR);
+/// `save` saves the tasks to the given data path.
+pub fn save(tasks: &Tasks, path: &Path) {
+    if let Err(err) = FileStore::new(path.to_path_buf()).save(tasks) {
+        output::fail(code::of_io(&err), format!("Data file, {err}"));
     };
 }
 
-/// `load` loads the tasks from the default data path.
-pub fn load(path: &PathBuf) -> Tasks {
-    let result: io::Result<Tasks> = import_file_or_create(&path, Format::Toml);
+/// `load` loads the tasks from the given data path.
+pub fn load(path: &Path) -> Tasks {
+    match FileStore::new(path.to_path_buf()).load() {
+        Ok(tasks) => tasks,
+        Err(err) => {
+            output::fail(code::of_io(&err), format!("Data file, {err}"));
+        }
+    }
+}
 
-    match result {
+/// `store` returns the store for the given data path in the way the configuration asks for.
+pub fn store(config: &Config, path: PathBuf) -> Box<dyn TaskStore> {
+    match config.get_storage() {
+        StorageMode::Snapshot => Box::new(FileStore::new(path)),
+        StorageMode::EventLog => Box::new(EventLogStore::new(path, config.get_compact_after())),
+    }
+}
+
+/// `lock` locks the given data files until the returned locks are dropped.
+/// If a file stays locked by another process, the program will exit.
+pub fn lock(paths: &[&Path]) -> Vec<FileLock> {
+    match FileLock::acquire_all(paths) {
+        Ok(locks) => locks,
+        Err(err) if err.kind() == ErrorKind::WouldBlock => {
+            output::fail(code::LOCK_BUSY, err);
+        }
+        Err(err) => {
+            output::fail(code::of_io(&err), format!("Lock file, {err}"));
+        }
+    }
+}
+
+/// `update` applies a change to the tasks of the list in use and saves it,
+/// holding the lock of its data file meanwhile.
+/// If the store cannot be read or written, the program will exit.
+pub fn update(config: &Config, change: impl FnMut(&mut Tasks)) -> Tasks {
+    let path = config.get_data_path();
+    let _lock = lock(&[&path]);
+
+    update_locked(config, path, change)
+}
+
+/// `update_locked` applies a change to the tasks of the given data path and saves it,
+/// then puts the removed tasks in its trash.
+/// The caller is expected to hold the lock of the data file, see `lock`.
+/// If the store cannot be read or written, the program will exit.
+pub fn update_locked(config: &Config, path: PathBuf, change: impl FnMut(&mut Tasks)) -> Tasks {
+    match try_update_locked(config, path, change) {
         Ok(tasks) => tasks,
         Err(err) => {
-            eprintln!("{} : Data file, {}", "Warning".red(), err.to_string());
-            process::exit(exitcode::IOERR);
+            output::fail(code::of_io(&err), format!("Data file, {err}"));
         }
     }
 }
+
+/// `try_update` applies a change to the tasks of the list in use and saves it like `update`,
+/// but returns the errors instead of exiting, for callers that keep running.
+pub fn try_update(config: &Config, change: impl FnMut(&mut Tasks)) -> io::Result<Tasks> {
+    let path = config.get_data_path();
+    let _locks = FileLock::acquire_all(&[&path])?;
+
+    try_update_locked(config, path, change)
+}
+
+/// `try_update_locked` works like `update_locked`, returning the errors instead of exiting.
+/// The trash is only written once the tasks are saved, so a failed save
+/// never leaves a task both in the list and in the trash.
+fn try_update_locked(
+    config: &Config,
+    path: PathBuf,
+    mut change: impl FnMut(&mut Tasks),
+) -> io::Result<Tasks> {
+    let mut trashed = vec![];
+
+    let tasks = store(config, path.clone()).update(&mut |tasks| {
+        change(tasks);
+        trashed = tasks.take_trashed();
+        Ok(())
+    })?;
+
+    trash::keep(config, &path, trashed)?;
+
+    Ok(tasks)
+}
+
+/// `try_save` writes the tasks of the list in use, kept in memory since they were loaded,
+/// holding the lock of its data file.
+/// `loaded` are the tasks as they were read or last written: if the saved ones differ,
+/// another process changed the list, nothing is written and false is returned.
+/// With the event log only the events recorded since the last save are appended,
+/// compacting the log every `compact_after` events, otherwise the data file is rewritten.
+/// The removed tasks are put in the trash once the tasks are written.
+pub fn try_save(config: &Config, tasks: &mut Tasks, loaded: &Tasks) -> io::Result<bool> {
+    let path = config.get_data_path();
+    let _locks = FileLock::acquire_all(&[&path])?;
+
+    let (saved, logged) = FileStore::new(path.clone()).load_with_log()?;
+
+    if !saved.iter().eq(loaded.iter()) {
+        return Ok(false);
+    }
+
+    let trashed = tasks.take_trashed();
+    let events = tasks.take_events();
+
+    match config.get_storage() {
+        StorageMode::Snapshot => FileStore::new(path.clone()).save(tasks)?,
+        StorageMode::EventLog => {
+            let total = logged + events.len();
+            event_log::append(&event_log::log_path(&path), events)?;
+
+            if total >= config.get_compact_after() {
+                EventLogStore::new(path.clone(), config.get_compact_after()).compact(tasks)?;
+            }
+        }
+    }
+
+    trash::keep(config, &path, trashed)?;
+
+    Ok(true)
+}
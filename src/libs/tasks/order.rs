@@ -0,0 +1,212 @@
+//! A module to handle the order of the tasks.
+//! The order of the list, as kept in the data file, is the manual order users curate with `todo move`.
+//! Sorting only changes how the tasks are shown, so the manual order is never lost
+//! and it decides between tasks that sort the same.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::libs::tasks::Tasks;
+
+/// `Sort` is the order the tasks are shown in.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Sort {
+    /// The order of the list.
+    #[default]
+    Manual,
+    /// By name, ignoring the case.
+    Name,
+    /// Pending tasks first.
+    Status,
+}
+
+/// `FromStr` is an implementation to parse the sort from the CLI.
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(sort: &str) -> Result<Self, Self::Err> {
+        match sort {
+            "manual" => Ok(Sort::Manual),
+            "name" => Ok(Sort::Name),
+            "status" => Ok(Sort::Status),
+            _ => Err(format!(
+                "unknown sort {sort:?}, expected manual, name or status"
+            )),
+        }
+    }
+}
+
+/// `Sort` is an implementation of the order of the tasks.
+impl Sort {
+    /// `label` returns the name of the sort.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Sort::Manual => "manual",
+            Sort::Name => "name",
+            Sort::Status => "status",
+        }
+    }
+
+    /// `sort` sorts task numbers in this order, keeping the manual order between equal tasks.
+    pub fn sort(&self, tasks: &Tasks, numbers: &mut [usize]) {
+        match self {
+            Sort::Manual => numbers.sort_unstable(),
+            Sort::Name => numbers.sort_by_key(|&number| {
+                (
+                    tasks.get(number).map(|task| task.name.to_lowercase()),
+                    number,
+                )
+            }),
+            Sort::Status => numbers.sort_by_key(|&number| {
+                (
+                    tasks.get(number).map(|task| task.get_is_completed()),
+                    number,
+                )
+            }),
+        }
+    }
+
+    /// `numbers` returns the numbers of all the tasks in this order.
+    pub fn numbers(&self, tasks: &Tasks) -> Vec<usize> {
+        let mut numbers: Vec<usize> = (0..tasks.len()).collect();
+        self.sort(tasks, &mut numbers);
+        numbers
+    }
+}
+
+/// `Place` is where a task is moved to in the manual order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Place {
+    /// First in the list.
+    Top,
+    /// Last in the list.
+    Bottom,
+    /// Right before the task with the given number.
+    Before(usize),
+    /// Right after the task with the given number.
+    After(usize),
+}
+
+/// `Place` is an implementation of the place of a task.
+impl Place {
+    /// `position` returns the number a task ends up with once moved here from `from`,
+    /// in a list of `len` tasks.
+    /// Returns None if the place is relative to the task itself.
+    pub fn position(&self, from: usize, len: usize) -> Option<usize> {
+        let shifted = |anchor: usize| if anchor > from { anchor - 1 } else { anchor };
+
+        match *self {
+            Place::Top => Some(0),
+            Place::Bottom => Some(len.saturating_sub(1)),
+            Place::Before(anchor) | Place::After(anchor) if anchor == from => None,
+            Place::Before(anchor) => Some(shifted(anchor)),
+            Place::After(anchor) => Some(shifted(anchor) + 1),
+        }
+    }
+
+    /// `anchor` returns the number of the task the place is relative to, if any.
+    pub fn anchor(&self) -> Option<usize> {
+        match *self {
+            Place::Before(anchor) | Place::After(anchor) => Some(anchor),
+            Place::Top | Place::Bottom => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::libs::tasks::event::Event;
+    use crate::libs::tasks::NotFound;
+
+    /// `tasks` returns a list of tasks named after their first number.
+    fn tasks(len: usize) -> Tasks {
+        let mut tasks = Tasks::new();
+
+        for number in 0..len {
+            tasks.add(&number.to_string(), &None);
+        }
+
+        tasks.take_events();
+        tasks
+    }
+
+    /// `names` returns the names of the tasks in order.
+    fn names(tasks: &Tasks) -> Vec<&str> {
+        tasks.iter().map(|task| task.name.as_str()).collect()
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(Place::Top.position(3, 5), Some(0));
+        assert_eq!(Place::Bottom.position(1, 5), Some(4));
+        assert_eq!(Place::Bottom.position(0, 0), Some(0));
+
+        assert_eq!(Place::Before(1).position(3, 5), Some(1));
+        assert_eq!(Place::After(1).position(3, 5), Some(2));
+        assert_eq!(Place::Before(4).position(1, 5), Some(3));
+        assert_eq!(Place::After(4).position(1, 5), Some(4));
+        assert_eq!(Place::Before(2).position(1, 5), Some(1));
+        assert_eq!(Place::After(0).position(1, 5), Some(1));
+
+        assert_eq!(Place::Before(2).position(2, 5), None);
+        assert_eq!(Place::After(2).position(2, 5), None);
+    }
+
+    #[test]
+    fn reorder() {
+        let cases = [
+            (4, Place::Top, Some(0), ["4", "0", "1", "2", "3"]),
+            (0, Place::Bottom, Some(4), ["1", "2", "3", "4", "0"]),
+            (4, Place::After(1), Some(2), ["0", "1", "4", "2", "3"]),
+            (0, Place::Before(3), Some(2), ["1", "2", "0", "3", "4"]),
+            (1, Place::After(3), Some(3), ["0", "2", "3", "1", "4"]),
+            (2, Place::Before(3), Some(2), ["0", "1", "2", "3", "4"]),
+            (2, Place::After(2), None, ["0", "1", "2", "3", "4"]),
+        ];
+
+        for (from, place, to, order) in cases {
+            let mut tasks = tasks(5);
+
+            assert_eq!(tasks.reorder(from, place), Ok(to), "{from} to {place:?}");
+            assert_eq!(names(&tasks), order, "{from} to {place:?}");
+        }
+    }
+
+    #[test]
+    fn reorder_missing_tasks() {
+        let mut tasks = tasks(3);
+
+        assert_eq!(tasks.reorder(3, Place::Top), Err(NotFound(3)));
+        assert_eq!(tasks.reorder(0, Place::After(5)), Err(NotFound(5)));
+        assert!(tasks.take_events().is_empty());
+    }
+
+    #[test]
+    fn replaying_moves_matches_reorder() {
+        let places = [
+            (4, Place::Top),
+            (0, Place::Bottom),
+            (3, Place::After(0)),
+            (1, Place::Before(4)),
+            (2, Place::Before(3)),
+            (2, Place::After(2)),
+        ];
+
+        let mut tasks = tasks(5);
+        let mut replayed = tasks.clone();
+
+        for (from, place) in places {
+            tasks.reorder(from, place).unwrap();
+        }
+
+        for event in tasks.take_events() {
+            assert!(matches!(event, Event::Moved { .. }), "{event:?}");
+            replayed.apply(event);
+        }
+
+        assert_eq!(names(&replayed), names(&tasks));
+    }
+}
@@ -0,0 +1,226 @@
+//! A module to handle the selection of tasks for the commands changing several at once.
+//! Tasks are selected by numbers, ranges of numbers like `7-10` and filters,
+//! the numbers being the ones shown by `todo list`.
+
+use std::str::FromStr;
+
+use crate::libs::tasks::Tasks;
+
+/// `Target` is a task number or an inclusive range of them, as given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A single task, like `3`.
+    Number(usize),
+    /// Every task from the first number to the last one, like `7-10`.
+    Range(usize, usize),
+}
+
+/// `FromStr` is an implementation to parse the target from the CLI.
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        let number = |text: &str| {
+            text.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("invalid task number {text:?}"))
+        };
+
+        match target.split_once('-') {
+            None => Ok(Target::Number(number(target)?)),
+            Some((first, last)) => {
+                let (first, last) = (number(first)?, number(last)?);
+
+                if first > last {
+                    return Err(format!("the range {target:?} goes backwards"));
+                }
+
+                Ok(Target::Range(first, last))
+            }
+        }
+    }
+}
+
+/// `Status` is the state of completed the selected tasks must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Completed,
+    Pending,
+}
+
+/// `Selection` is the set of tasks a command works on.
+/// Tasks are selected if they are in one of the targets, or in any when there are none,
+/// and they pass every filter given.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub targets: Vec<Target>,
+    pub status: Option<Status>,
+    pub matching: Option<String>,
+}
+
+/// `Selection` is an implementation of the selection.
+impl Selection {
+    /// `is_empty` checks if nothing was given to select tasks with, then every task is selected.
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty() && self.status.is_none() && self.matching.is_none()
+    }
+
+    /// `indexes` returns the numbers of the selected tasks in order, each one once.
+    /// If a target points past the last task, the first missing number is returned as the error.
+    pub fn indexes(&self, tasks: &Tasks) -> Result<Vec<usize>, usize> {
+        let mut indexes: Vec<usize> = if self.targets.is_empty() {
+            (0..tasks.len()).collect()
+        } else {
+            let mut indexes = vec![];
+
+            for target in &self.targets {
+                let (first, last) = match *target {
+                    Target::Number(number) => (number, number),
+                    Target::Range(first, last) => (first, last),
+                };
+
+                if last >= tasks.len() {
+                    return Err(first.max(tasks.len()));
+                }
+
+                indexes.extend(first..=last);
+            }
+
+            indexes.sort_unstable();
+            indexes.dedup();
+            indexes
+        };
+
+        let matching = self.matching.as_ref().map(|text| text.to_lowercase());
+
+        indexes.retain(|&index| {
+            let Some(task) = tasks.get(index) else {
+                return false;
+            };
+
+            let status = match self.status {
+                Some(Status::Completed) => task.get_is_completed(),
+                Some(Status::Pending) => !task.get_is_completed(),
+                None => true,
+            };
+
+            let matches = matching.as_ref().is_none_or(|text| {
+                task.name.to_lowercase().contains(text)
+                    || task.description.to_lowercase().contains(text)
+            });
+
+            status && matches
+        });
+
+        Ok(indexes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `tasks` returns a list of tasks, the ones at the given numbers completed.
+    fn tasks(names: &[&str], completed: &[usize]) -> Tasks {
+        let mut tasks = Tasks::new();
+
+        for name in names {
+            tasks.add(name, &Some(format!("About {name}")));
+        }
+
+        for &index in completed {
+            tasks.toggle_completed(index).unwrap();
+        }
+
+        tasks
+    }
+
+    #[test]
+    fn targets() {
+        assert_eq!("3".parse(), Ok(Target::Number(3)));
+        assert_eq!("7-10".parse(), Ok(Target::Range(7, 10)));
+        assert_eq!("4-4".parse(), Ok(Target::Range(4, 4)));
+        assert_eq!(" 3 ".parse(), Ok(Target::Number(3)));
+        assert_eq!("7 - 10".parse(), Ok(Target::Range(7, 10)));
+    }
+
+    #[test]
+    fn invalid_targets() {
+        assert_eq!(
+            "10-7".parse::<Target>(),
+            Err("the range \"10-7\" goes backwards".to_string())
+        );
+        assert!("".parse::<Target>().is_err());
+        assert!("abc".parse::<Target>().is_err());
+        assert!("-3".parse::<Target>().is_err());
+        assert!("3-".parse::<Target>().is_err());
+        assert!("1-2-3".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn targets_are_sorted_and_deduplicated() {
+        let tasks = tasks(&["a", "b", "c", "d", "e"], &[]);
+        let selection = Selection {
+            targets: vec![Target::Number(3), Target::Range(1, 3), Target::Number(1)],
+            ..Selection::default()
+        };
+
+        assert_eq!(selection.indexes(&tasks), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn no_targets_select_every_task() {
+        let tasks = tasks(&["a", "b", "c"], &[]);
+
+        assert!(Selection::default().is_empty());
+        assert_eq!(Selection::default().indexes(&tasks), Ok(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn missing_tasks() {
+        let tasks = tasks(&["a", "b", "c"], &[]);
+        let missing = |target| {
+            Selection {
+                targets: vec![Target::Number(0), target],
+                ..Selection::default()
+            }
+            .indexes(&tasks)
+        };
+
+        assert_eq!(missing(Target::Number(5)), Err(5));
+        assert_eq!(missing(Target::Range(1, 4)), Err(3));
+        assert_eq!(missing(Target::Range(6, 8)), Err(6));
+    }
+
+    #[test]
+    fn filters() {
+        let tasks = tasks(&["Write report", "Buy milk", "Read report"], &[0]);
+        let select = |status, matching: Option<&str>| {
+            Selection {
+                targets: vec![],
+                status,
+                matching: matching.map(String::from),
+            }
+            .indexes(&tasks)
+        };
+
+        assert_eq!(select(Some(Status::Completed), None), Ok(vec![0]));
+        assert_eq!(select(Some(Status::Pending), None), Ok(vec![1, 2]));
+        assert_eq!(select(None, Some("REPORT")), Ok(vec![0, 2]));
+        assert_eq!(select(None, Some("about buy")), Ok(vec![1]));
+        assert_eq!(select(Some(Status::Pending), Some("report")), Ok(vec![2]));
+        assert_eq!(select(Some(Status::Completed), Some("milk")), Ok(vec![]));
+    }
+
+    #[test]
+    fn filters_narrow_the_targets() {
+        let tasks = tasks(&["a", "b", "c", "d"], &[1, 3]);
+        let selection = Selection {
+            targets: vec![Target::Range(0, 2)],
+            status: Some(Status::Completed),
+            matching: None,
+        };
+
+        assert_eq!(selection.indexes(&tasks), Ok(vec![1]));
+    }
+}
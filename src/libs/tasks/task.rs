@@ -1,19 +1,22 @@
+//! A module to handle the tasks of the application.
+
 use fmt::Debug;
 use std::cell::Cell;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
-use indoc::indoc;
 use serde::{Deserialize, Serialize};
 
-/// A module to handle the tasks of the application.
+use crate::libs::render::template;
 
 /// `Task` is a struct that contains the information of a task.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Task {
     pub name: String,
     pub description: String,
     is_completed: Cell<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<u64>,
 }
 
 /// `Task` is an implementation of the task.
@@ -24,11 +27,12 @@ impl Task {
             name,
             description,
             is_completed: Cell::from(false),
+            completed_at: None,
         }
     }
 
     /// `completed_symbol` returns the symbol of the completed state.
-    fn completed_symbol(&self) -> String {
+    pub fn completed_symbol(&self) -> String {
         if self.is_completed.get() {
             String::from("✓")
         } else {
@@ -41,6 +45,24 @@ impl Task {
         self.is_completed.set(!self.is_completed.get());
     }
 
+    /// `set_completed` sets the state of completed for a task.
+    pub fn set_completed(&self, completed: bool) {
+        self.is_completed.set(completed);
+    }
+
+    /// `mark_completed` sets the state of completed for a task and when it was completed,
+    /// in seconds since the UNIX epoch.
+    pub fn mark_completed(&mut self, completed: bool, at: Option<u64>) {
+        self.is_completed.set(completed);
+        self.completed_at = if completed { at } else { None };
+    }
+
+    /// `get_completed_at` returns when the task was completed, in seconds since the UNIX epoch.
+    /// Pending tasks, and tasks completed before this was recorded, have none.
+    pub fn get_completed_at(&self) -> Option<u64> {
+        self.completed_at
+    }
+
     /// `get_is_completed` returns the state of completed for a task.
     pub fn get_is_completed(&self) -> bool {
         self.is_completed.get()
@@ -53,19 +75,10 @@ impl Task {
 }
 
 /// `Display` is an implementation of the display for `Task`.
+/// Refer to `TASK_LAYOUT` for the layout.
 impl Display for Task {
     /// `fmt` formats the output of `Task`.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            indoc! {"
-            Task Name   : {}
-            Description : {}
-            Completed   : {}
-            "},
-            self.name,
-            self.description,
-            self.completed_symbol()
-        )
+        write!(f, "{}", template::task_layout().render_task(0, self))
     }
 }
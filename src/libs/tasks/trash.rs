@@ -0,0 +1,250 @@
+//! A module to handle the trash of a list.
+//! Removed tasks are kept with the time they were removed in a file next to the data file of the list,
+//! named like it with `.trash` appended, until they are restored, the trash is emptied
+//! or they have been there for more than `trash_retention_days` days.
+//! The trash is only written while holding the lock of its data file.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_any::Format;
+use serde_json::Value;
+
+use crate::libs::config::Config;
+use crate::libs::output::{self, code};
+use crate::libs::storage::event_log::with_suffix;
+use crate::libs::storage::export_file;
+use crate::libs::storage::migration::{self, read_file_versioned_raw};
+use crate::libs::tasks::task::Task;
+use crate::libs::tasks::{self, Tasks, DAY};
+
+/// `Trashed` is a removed task and when it was removed.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Trashed {
+    #[serde(flatten)]
+    pub task: Task,
+    /// Seconds since the UNIX epoch.
+    pub removed_at: u64,
+}
+
+/// `Trash` is the removed tasks of a list, the oldest first.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Trash {
+    #[serde(default = "current_version")]
+    version: u32,
+    #[serde(default)]
+    tasks: Vec<Trashed>,
+}
+
+/// `current_version` returns the version of the trash written by this build.
+fn current_version() -> u32 {
+    migration::TRASH.version()
+}
+
+/// `Default` is an implementation of the default trash, an empty one.
+impl Default for Trash {
+    fn default() -> Self {
+        Trash {
+            version: current_version(),
+            tasks: vec![],
+        }
+    }
+}
+
+/// `Trash` is an implementation of the trash.
+impl Trash {
+    /// `iter` returns an iterator over the removed tasks in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Trashed> {
+        self.tasks.iter()
+    }
+
+    /// `len` returns the number of removed tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Checks if the trash is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// `tasks` returns the removed tasks as a list, to select or show them like any other.
+    pub fn tasks(&self) -> Tasks {
+        let mut tasks = Tasks::new();
+
+        for trashed in &self.tasks {
+            tasks.push(trashed.task.clone());
+        }
+
+        tasks.take_events();
+        tasks
+    }
+
+    /// `purge` drops the tasks removed more than the given days ago.
+    fn purge(&mut self, days: u64) {
+        let after = tasks::now().saturating_sub(days * DAY);
+        self.tasks.retain(|trashed| trashed.removed_at > after);
+    }
+}
+
+/// `trash_path` returns the path of the trash of a data file.
+///
+/// # Example
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use todo::libs::tasks::trash::trash_path;
+///
+/// assert_eq!(trash_path(Path::new("task.list")), PathBuf::from("task.list.trash"));
+/// ```
+pub fn trash_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".trash")
+}
+
+/// `load` loads the trash of the list in use, without the tasks past the retention.
+/// If it cannot be read, the program will exit.
+pub fn load(config: &Config) -> Trash {
+    match try_load(config, &config.get_data_path()) {
+        Ok(trash) => trash,
+        Err(err) => output::fail(code::of_io(&err), format!("Trash, {err}")),
+    }
+}
+
+/// `keep` puts removed tasks of the given data path in its trash,
+/// purging the tasks past the retention.
+/// The caller is expected to hold the lock of the data file.
+pub fn keep(config: &Config, path: &Path, removed: Vec<Task>) -> io::Result<()> {
+    if removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut trash = try_load(config, path)?;
+    let removed_at = tasks::now();
+
+    trash
+        .tasks
+        .extend(removed.into_iter().map(|task| Trashed { task, removed_at }));
+
+    try_save(path, &trash)
+}
+
+/// `restore` moves the removed tasks `choose` returns the numbers of
+/// back to the end of the list in use.
+/// Returns the restored tasks with their numbers in the trash.
+/// If the trash or the list cannot be read or written, the program will exit.
+pub fn restore(
+    config: &Config,
+    choose: impl FnOnce(&Tasks) -> Vec<usize>,
+) -> Vec<(usize, Trashed)> {
+    let path = config.get_data_path();
+    let _locks = tasks::io::lock(&[&path]);
+
+    match try_restore(config, &path, choose) {
+        Ok(restored) => restored,
+        Err(err) => output::fail(code::of_io(&err), format!("Trash, {err}")),
+    }
+}
+
+/// `empty` deletes every task of the trash of the list in use for good, once `approve` agrees.
+/// `approve` is asked before taking the lock, so other commands are not kept waiting,
+/// and only the tasks it was shown are deleted, the ones removed meanwhile are kept.
+/// Returns the deleted tasks, or None if they were not approved.
+/// If the trash cannot be read or written, the program will exit.
+pub fn empty(config: &Config, approve: impl FnOnce(&Trash) -> bool) -> Option<Trash> {
+    let path = config.get_data_path();
+
+    let approved = match try_load(config, &path) {
+        Ok(trash) if !trash.is_empty() && approve(&trash) => trash,
+        Ok(_) => return None,
+        Err(err) => output::fail(code::of_io(&err), format!("Trash, {err}")),
+    };
+
+    let _locks = tasks::io::lock(&[&path]);
+
+    let result = try_load(config, &path).and_then(|mut trash| {
+        let (deleted, kept) = trash
+            .tasks
+            .drain(..)
+            .partition(|trashed| approved.tasks.contains(trashed));
+        trash.tasks = kept;
+
+        try_save(&path, &trash).map(|_| Trash {
+            tasks: deleted,
+            ..Trash::default()
+        })
+    });
+
+    match result {
+        Ok(deleted) => Some(deleted),
+        Err(err) => output::fail(code::of_io(&err), format!("Trash, {err}")),
+    }
+}
+
+/// `try_restore` moves removed tasks back to the list like `restore`, returning the errors.
+/// The tasks are added to the list before they leave the trash,
+/// so a failure midway never loses them.
+fn try_restore(
+    config: &Config,
+    path: &Path,
+    choose: impl FnOnce(&Tasks) -> Vec<usize>,
+) -> io::Result<Vec<(usize, Trashed)>> {
+    let mut trash = try_load(config, path)?;
+    let indexes = choose(&trash.tasks());
+
+    let restored: Vec<(usize, Trashed)> = indexes
+        .iter()
+        .map(|&index| (index, trash.tasks[index].clone()))
+        .collect();
+
+    if restored.is_empty() {
+        return Ok(restored);
+    }
+
+    tasks::io::store(config, path.to_path_buf()).update(&mut |tasks| {
+        for (_, trashed) in &restored {
+            tasks.push(trashed.task.clone());
+        }
+
+        Ok(())
+    })?;
+
+    for &index in indexes.iter().rev() {
+        trash.tasks.remove(index);
+    }
+
+    try_save(path, &trash)?;
+
+    Ok(restored)
+}
+
+/// `try_load` reads the trash of a data file, without the tasks past the retention,
+/// returning the errors instead of exiting. A missing trash is empty.
+/// A trash from an older version is upgraded in memory, it is written at the current one.
+pub fn try_load(config: &Config, path: &Path) -> io::Result<Trash> {
+    let mut trash: Trash =
+        match read_file_versioned_raw(&trash_path(path), Format::Toml, &migration::TRASH) {
+            Ok(data) => serde_json::from_value(Value::Object(data))
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Trash::default(),
+            Err(err) => return Err(err),
+        };
+
+    trash.purge(config.get_trash_retention_days());
+    Ok(trash)
+}
+
+/// `try_save` writes the trash of a data file, removing the file once the trash is empty.
+fn try_save(path: &Path, trash: &Trash) -> io::Result<()> {
+    let file = trash_path(path);
+
+    if !trash.is_empty() {
+        return export_file(trash, Format::Toml, &file);
+    }
+
+    match fs::remove_file(&file) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
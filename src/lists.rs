@@ -0,0 +1,192 @@
+//! A module to handle the named task lists
+//! from the command line.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use clap_complete::engine::ArgValueCandidates;
+use serde::Serialize;
+use serde_json::json;
+use todo::libs::config::Config;
+use todo::libs::output::code;
+use todo::libs::render::theme::{paint, theme};
+use todo::libs::storage::event_log::{history_path, log_path};
+use todo::libs::storage::lock::lock_path;
+use todo::libs::tasks::archive::archive_path;
+use todo::libs::tasks::trash::trash_path;
+use todo::libs::{config, output, tasks};
+
+use crate::completions::list_names;
+
+#[derive(Subcommand)]
+pub enum ListsCommands {
+    /// Create a new list
+    Create {
+        /// The name of the list
+        name: String,
+
+        /// The path to the data, by default next to the main list, a relative one is taken from the current directory
+        #[arg(long, short)]
+        path: Option<PathBuf>,
+    },
+
+    /// Rename a list, its data file is kept in place
+    Rename {
+        /// The current name of the list
+        #[arg(add = ArgValueCandidates::new(list_names))]
+        name: String,
+
+        /// The new name of the list
+        new_name: String,
+    },
+
+    /// Delete a list
+    Delete {
+        /// The name of the list
+        #[arg(add = ArgValueCandidates::new(list_names))]
+        name: String,
+
+        /// Also delete the data file of the list, with its event log, its archive and its trash
+        #[arg(long)]
+        purge: bool,
+    },
+
+    /// Set the list used when --list is not given
+    Default {
+        /// The name of the list
+        #[arg(add = ArgValueCandidates::new(list_names))]
+        name: String,
+    },
+}
+
+/// `lists_commands` is a convenience function for managing the named lists.
+/// Without a command, it enumerates the lists with their task counts.
+///
+/// Refer to the `ListsCommands` struct for more information about parameters.
+///
+/// # Example
+///
+/// ```
+/// lists_commands(Some(ListsCommands::Create { name: String::from("work"), path: None }), config::io::load());
+/// ```
+pub(crate) fn lists_commands(command: Option<ListsCommands>, effective: Config) {
+    let command = match command {
+        Some(command) => command,
+        None => return print_lists(&effective),
+    };
+
+    let mut config = config::io::load_user();
+
+    match command {
+        ListsCommands::Create { name, path } => {
+            let path = path.map(|path| config::paths::from_current_dir(&path));
+            config.create_list(&name, path);
+            let path = config.get_list_path(&name);
+
+            output::report(&json!({ "created": name, "path": path }), || {
+                println!("{name} - {path:?}")
+            });
+        }
+
+        ListsCommands::Rename { name, new_name } => {
+            config.rename_list(&name, &new_name);
+            output::report(&json!({ "renamed": name, "to": new_name }), || {
+                println!("{name} -> {new_name}")
+            });
+        }
+
+        ListsCommands::Delete { name, purge } => {
+            let path = config.delete_list(&name);
+
+            if purge {
+                purge_data(&path);
+            }
+
+            output::report(&json!({ "deleted": name, "purged": purge }), || {
+                println!("{name} - deleted")
+            });
+        }
+
+        ListsCommands::Default { name } => {
+            config.set_default_list(&name);
+
+            output::report(&json!({ "default": name }), || println!("{name} - default"));
+        }
+    }
+
+    config::io::save(&config.validate())
+}
+
+/// `ListSummary` is a list with its task counts, as shown by `todo lists`.
+#[derive(Serialize)]
+struct ListSummary {
+    name: String,
+    default: bool,
+    tasks: usize,
+    completed: usize,
+    path: PathBuf,
+}
+
+/// `print_lists` prints every list with its task counts, marking the default one.
+fn print_lists(config: &Config) {
+    let mut lists = vec![];
+
+    for name in config.lists() {
+        let path = config.get_list_path(&name);
+
+        let (total, completed) = if path.exists() {
+            let tasks = tasks::io::load(&path);
+            let completed = tasks.iter().filter(|task| task.get_is_completed()).count();
+            (tasks.len(), completed)
+        } else {
+            (0, 0)
+        };
+
+        lists.push(ListSummary {
+            default: name == config.get_default_list(),
+            name,
+            tasks: total,
+            completed,
+            path,
+        });
+    }
+
+    output::report(&json!({ "lists": lists }), || {
+        for list in &lists {
+            let marker = if list.default {
+                paint("*", &theme().highlight)
+            } else {
+                paint(" ", "")
+            };
+
+            println!(
+                "{marker} {} : {} tasks, {} completed - {:?}",
+                list.name, list.tasks, list.completed, list.path
+            );
+        }
+    });
+}
+
+/// `purge_data` removes the data file of a deleted list, its event log and history, its archive, its trash and their lock files.
+fn purge_data(path: &Path) {
+    for file in [
+        path.to_path_buf(),
+        log_path(path),
+        history_path(path),
+        archive_path(path),
+        trash_path(path),
+        lock_path(path),
+        lock_path(&archive_path(path)),
+        lock_path(&trash_path(path)),
+    ] {
+        if let Err(err) = fs::remove_file(&file) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                output::fail(
+                    code::of_io(&err),
+                    format!("Could not delete {:?}, {}", file, err),
+                );
+            }
+        }
+    }
+}
@@ -1,26 +1,158 @@
+mod archive;
+mod batch;
+mod completions;
 mod conversion;
+mod lists;
+mod man;
+mod shell;
+mod transfer;
+mod trash;
+mod tui;
 
+use crate::archive::{archive_commands, auto_archive, unarchive_commands};
+use crate::batch::{batch_commands, Batch, SelectArgs};
+use crate::completions::{
+    archived_numbers, complete_env, completions_commands, list_names, task_numbers,
+};
 use crate::conversion::{convert_commands, ConvertCommand};
-use clap::{Parser, Subcommand};
-use colored::Colorize;
+use crate::lists::{lists_commands, ListsCommands};
+use crate::man::man_commands;
+use crate::shell::shell_commands;
+use crate::transfer::{reorder_commands, transfer_commands, PlaceArgs, Transfer};
+use crate::trash::{trash_commands, TrashCommands};
+use crate::tui::tui_commands;
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand};
+use clap_complete::engine::ArgValueCandidates;
+use serde_json::{json, Value};
 use std::path::PathBuf;
-use todo::libs::tasks::Tasks;
-use todo::libs::{config, tasks};
+use std::{env, process};
+use terminal_size::Width;
+use todo::libs::config::{layers, Config, StorageMode};
+use todo::libs::output::{code, ColorChoice, OutputFormat, TaskRecord};
+use todo::libs::render::table::{Column, Overflow, Table};
+use todo::libs::render::template::Template;
+use todo::libs::render::theme;
+use todo::libs::render::{Renderer, View};
+use todo::libs::tasks::order::Sort;
+use todo::libs::tasks::selection::{Selection, Status};
+use todo::libs::tasks::task::Task;
+use todo::libs::tasks::{NotFound, Tasks};
+use todo::libs::{config, output, project, render, tasks};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    #[command(flatten)]
+    pub globals: GlobalArgs,
+}
+
+#[derive(Args)]
+pub struct GlobalArgs {
+    /// The list to use instead of the default one
+    #[arg(long, global = true, add = ArgValueCandidates::new(list_names))]
+    pub list: Option<String>,
+
+    /// Ignore the project list found in the current directory or its parents
+    #[arg(long, global = true)]
+    pub global: bool,
+
+    /// Override a configuration value for this run, can be repeated
+    #[arg(
+        short = 'c',
+        long = "config-option",
+        value_name = "KEY=VALUE",
+        global = true
+    )]
+    pub config_options: Vec<String>,
+
+    /// The user config file to use, instead of TODO_CONFIG or the default one
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config_path: Option<PathBuf>,
+
+    /// The format of the output, human, json, yaml or tsv
+    #[arg(long, value_name = "FORMAT", default_value = "human", global = true)]
+    pub output: OutputFormat,
+
+    /// When to use colors, auto, always or never
+    #[arg(long, value_name = "WHEN", default_value = "auto", global = true)]
+    pub color: ColorChoice,
+}
+
+/// `GlobalArgs` is an implementation of the global options.
+impl GlobalArgs {
+    /// `to_args` returns the options as they are given on the command line,
+    /// to run another command with the same ones.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if let Some(list) = &self.list {
+            args.extend([String::from("--list"), list.clone()]);
+        }
+
+        if self.global {
+            args.push(String::from("--global"));
+        }
+
+        for pair in &self.config_options {
+            args.extend([String::from("-c"), pair.clone()]);
+        }
+
+        if let Some(path) = &self.config_path {
+            args.extend([String::from("--config"), path.display().to_string()]);
+        }
+
+        args.extend([String::from("--output"), self.output.to_string()]);
+        args
+    }
+
+    /// `overrides` returns the `-c` options as keys and raw values,
+    /// exiting with CONFIG_ERROR error code if one is not a `key=value` pair.
+    pub fn overrides(&self) -> Vec<(String, String)> {
+        self.config_options
+            .iter()
+            .map(|pair| {
+                layers::parse_override(pair)
+                    .unwrap_or_else(|message| output::fail(code::CONFIG_ERROR, message))
+            })
+            .collect()
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// List all tasks
     List {
-        /// Print in a one line for task
-        #[arg(long, short)]
+        /// Print in a one line for task, same as --format short
+        #[arg(long, short, conflicts_with = "format")]
         short: bool,
+
+        /// How to show the tasks, long, short, table, tree or json
+        #[arg(long, short, value_name = "FORMAT")]
+        format: Option<View>,
+
+        /// The template of every line, like "{id:>3} {status} {name}"
+        #[arg(long, short, value_name = "TEMPLATE", value_parser = Template::parse, conflicts_with_all = ["format", "short"])]
+        template: Option<Template>,
+
+        /// The columns of the table and their order, like id,status,name
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+        columns: Vec<Column>,
+
+        /// Wrap long text in the table instead of truncating it
+        #[arg(long)]
+        wrap: bool,
+
+        /// List the archived tasks instead
+        #[arg(long)]
+        archived: bool,
+
+        /// The order of the tasks, manual, name or status, the numbers stay the same
+        #[arg(long, value_name = "SORT")]
+        sort: Option<Sort>,
     },
 
     /// Add a new Task
@@ -33,19 +165,119 @@ pub enum Commands {
         description: Option<String>,
     },
 
-    /// To remove a task
+    /// To remove tasks
     Remove {
-        /// The task number
-        number: usize,
+        #[command(flatten)]
+        select: SelectArgs,
+
+        /// Remove without asking, needed to remove more than one task from scripts
+        #[arg(long, short, visible_alias = "yes", short_alias = 'y')]
+        force: bool,
     },
 
     /// Remove all tasks
-    Clean,
+    Clean {
+        /// Only remove the completed tasks
+        #[arg(long)]
+        completed: bool,
+
+        /// Remove without asking, needed to remove more than one task from scripts
+        #[arg(long, short, visible_alias = "yes", short_alias = 'y')]
+        force: bool,
+    },
 
-    /// Toggles the state of completed for a task
+    /// Toggles the state of completed for tasks
     Toggle {
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+
+    /// Mark tasks as completed
+    Done {
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+
+    /// Move the completed tasks, or the selected ones, to the archive of the list
+    #[command(mut_group("selection", |group| group.required(false)))]
+    Archive {
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+
+    /// Move archived tasks back to the list, the numbers are the ones of list --archived
+    #[command(mut_arg("targets", |arg| arg.add(ArgValueCandidates::new(archived_numbers))))]
+    Unarchive {
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+
+    /// List the removed tasks, or restore them or empty the trash with a subcommand
+    Trash {
+        #[command(subcommand)]
+        command: Option<TrashCommands>,
+    },
+
+    /// Move a task to another list or data file, or to another place in the list
+    ///
+    /// The manual order is the order of the tasks in the data file, there is no separate order key.
+    /// Moving a task in the list renumbers the tasks between its old and its new place,
+    /// so check the numbers with todo list before the next command.
+    Move {
         /// The task number
+        #[arg(add = ArgValueCandidates::new(task_numbers))]
         number: usize,
+
+        #[command(flatten)]
+        place: PlaceArgs,
+    },
+
+    /// Copy a task to another list or data file
+    Copy {
+        /// The task number
+        #[arg(add = ArgValueCandidates::new(task_numbers))]
+        number: usize,
+
+        /// The name of the list, or the path of the data file, to copy the task to
+        #[arg(long, add = ArgValueCandidates::new(list_names))]
+        to: String,
+    },
+
+    /// Open a full-screen interface to browse and change the tasks
+    Tui,
+
+    /// Open a shell to run commands without the todo prefix, keeping the tasks loaded
+    Shell,
+
+    /// Print the completion script of a shell, to be sourced from its startup file
+    Completions {
+        /// The shell, bash, elvish, fish, powershell or zsh
+        #[arg(value_parser = PossibleValuesParser::new(completions::SHELLS))]
+        shell: String,
+    },
+
+    /// Print the manual page of todo or of a command, or write them all to a directory
+    Man {
+        /// The command to show the page of, like config set
+        #[arg(conflicts_with = "out_dir")]
+        command: Vec<String>,
+
+        /// Write the pages of todo and of every command to this directory
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Create a project list in the current directory
+    Init {
+        /// Create a .todo directory instead of a single .todo file
+        #[arg(long)]
+        directory: bool,
+    },
+
+    /// List the task lists, or manage them with a subcommand
+    Lists {
+        #[command(subcommand)]
+        command: Option<ListsCommands>,
     },
 
     #[command(subcommand)]
@@ -63,9 +295,51 @@ pub enum Commands {
 pub enum ConfigCommands {
     /// Modify the path where the data is stored, put None to reset to default
     DataPath {
-        /// The path to the data
+        /// The path to the data, can start with ~ or $VAR, a relative one is taken from the current directory
         path: Option<PathBuf>,
     },
+
+    /// Choose how changes are written, rewriting the data file or appending to an event log
+    Storage {
+        /// The storage mode, snapshot or event-log
+        mode: StorageMode,
+
+        /// Compact the event log into the data file after this many events
+        #[arg(long)]
+        compact_after: Option<usize>,
+    },
+
+    /// Print the effective value of a key
+    Get {
+        /// The key, nested keys are separated by dots, like lists.work
+        key: String,
+    },
+
+    /// Set a key in the user config file
+    Set {
+        /// The key, nested keys are separated by dots, like lists.work
+        key: String,
+
+        /// The value, checked against the type of the key
+        value: String,
+    },
+
+    /// Remove a key from the user config file, going back to the default
+    Unset {
+        /// The key, nested keys are separated by dots, like lists.work
+        key: String,
+    },
+
+    /// List every key with its effective value
+    #[command(visible_alias = "show")]
+    List {
+        /// Also show the layer every value comes from
+        #[arg(long)]
+        origin: bool,
+    },
+
+    /// Check the configuration and report every problem found
+    Check,
 }
 
 /// `config_commands` is a convenience function for configuring the config file.
@@ -75,82 +349,431 @@ pub enum ConfigCommands {
 /// # Example
 ///
 /// ```
-/// config_commands(ConfigCommands::DataPath { path: Some(PathBuf::from("data")) });
+/// config_commands(ConfigCommands::DataPath { path: Some(PathBuf::from("data")) }, &globals);
 /// ```
-fn config_commands(command: ConfigCommands) {
-    let mut config = config::io::load();
-
-    config = match command {
+fn config_commands(command: ConfigCommands, globals: &GlobalArgs) {
+    match command {
         ConfigCommands::DataPath { path } => {
-            config.set_data_path(path);
-            config.validate()
+            let path = path.map(|path| config::paths::from_current_dir(&path));
+            edit_config(|config| config.set_data_path(path))
+        }
+
+        ConfigCommands::Storage {
+            mode,
+            compact_after,
+        } => edit_config(|config| config.set_storage(mode, compact_after)),
+
+        ConfigCommands::Get { key } => {
+            let value = config::io::get_value(&globals.overrides(), &key);
+
+            output::report(&json!({ "key": key, "value": value }), || {
+                if let Some(value) = &value {
+                    print_value(&key, value);
+                }
+            });
+        }
+
+        ConfigCommands::Set { key, value } => {
+            config::io::set_value(&key, &value);
+            output::report(&json!({ "key": key, "value": value }), || {});
+        }
+
+        ConfigCommands::Unset { key } => {
+            let removed = config::io::unset_value(&key);
+
+            output::report(&json!({ "key": key, "removed": removed }), || {
+                if !removed {
+                    println!("{key} was not set in the user config file.");
+                }
+            });
+        }
+
+        ConfigCommands::List { origin } => show_config(globals, origin),
+
+        ConfigCommands::Check => check_config(globals),
+    }
+}
+
+/// `check_config` reports every problem of the configuration with a suggestion to fix it.
+/// If any of them is an error, it will exit with CONFIG error code.
+fn check_config(globals: &GlobalArgs) {
+    let issues = config::io::check(&globals.overrides());
+
+    output::report(&json!({ "issues": issues }), || {
+        if issues.is_empty() {
+            println!("No issues found in {:?}.", config::io::config_path());
+        } else {
+            config::check::report(&issues);
+        }
+    });
+
+    if issues.iter().any(config::check::Issue::is_error) {
+        process::exit(code::CONFIG_ERROR);
+    }
+}
+
+/// `print_value` prints a configuration value, text without quotes and tables as dotted keys.
+fn print_value(key: &str, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::String(text) => println!("{text}"),
+        Value::Object(table) => {
+            for (inner, value) in table {
+                println!("{key}.{inner} = {value}");
+            }
+        }
+        _ => println!("{value}"),
+    }
+}
+
+/// `edit_config` applies a change to the user config file and saves it once validated.
+fn edit_config(change: impl FnOnce(&mut Config)) {
+    let mut config = config::io::load_user();
+
+    change(&mut config);
+
+    let config = config.validate();
+    config::io::save(&config);
+
+    output::report(&json!({ "config": config }), || {});
+}
+
+/// `show_config` prints every effective configuration value,
+/// optionally with the layer it comes from.
+fn show_config(globals: &GlobalArgs, origin: bool) {
+    let entries = config::io::load_layered(&globals.overrides()).entries();
+
+    if !output::is_human() {
+        let entries: Vec<Value> = entries
+            .into_iter()
+            .map(|(key, value, from)| json!({ "key": key, "value": value, "origin": from.to_string() }))
+            .collect();
+
+        return output::emit(&json!({ "entries": entries }));
+    }
+
+    let lines: Vec<(String, String)> = entries
+        .into_iter()
+        .map(|(key, value, from)| (format!("{key} = {value}"), from.to_string()))
+        .collect();
+
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+
+    for (line, from) in lines {
+        if origin {
+            println!("{line:width$}  # {from}");
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+/// `load_config` loads the configuration and selects the list given with `--list`, if any.
+/// Otherwise, unless `--global` is given, the project list of the current directory is used.
+fn load_config(globals: &GlobalArgs) -> Config {
+    let mut config = config::io::load_layered(&globals.overrides()).config;
+
+    if let Some(name) = &globals.list {
+        config.select_list(name);
+    } else if !globals.global {
+        if let Some(path) = env::current_dir()
+            .ok()
+            .and_then(|dir| project::discover(&dir))
+        {
+            config.use_project(path);
         }
-    };
+    }
 
-    config::io::save(&config)
+    config
+}
+
+/// `init_commands` creates a project list in the current directory.
+///
+/// # Example
+///
+/// ```
+/// init_commands(false);
+/// ```
+fn init_commands(directory: bool) {
+    let result = env::current_dir().and_then(|dir| project::init(&dir, directory));
+
+    match result {
+        Ok(path) => output::report(&json!({ "path": path }), || {
+            println!("Initialized project list in {path:?}")
+        }),
+        Err(err) => {
+            output::fail(code::of_io(&err), err);
+        }
+    }
 }
 
 /// `tasks_commands` is a convenience function for managing tasks.
 /// Refer to the `Commands` struct for more information about parameters.
 /// To specific functionality refer to the `Tasks` struct.
+/// After a command changing the tasks, the ones completed long ago are archived if configured,
+/// so `list` only reads the list and the numbers it shows stay valid for the next command.
 ///
 /// # Example
 ///
 /// ```
-/// tasks_commands(Commands::List { short: false });
+/// tasks_commands(Commands::List { short: false, format: None, template: None, columns: vec![], wrap: false, archived: false, sort: None }, config::io::load());
 /// ```
-fn tasks_commands(commands: Commands) {
-    let config = config::io::load();
-    let mut tasks = tasks::io::load(&config.get_data_path());
+fn tasks_commands(commands: Commands, config: Config) {
+    let changes = !matches!(commands, Commands::List { .. });
 
     match commands {
-        Commands::List { short } => {
-            if short {
-                tasks.list_short();
+        Commands::List {
+            short,
+            format,
+            template,
+            columns,
+            wrap,
+            archived,
+            sort,
+        } => {
+            let tasks = if archived {
+                tasks::archive::load(&config)
             } else {
-                tasks.list_long();
-            }
+                tasks::io::load(&config.get_data_path())
+            };
+            let renderer = list_renderer(&config, short, format, template, columns, wrap);
+
+            print_tasks(
+                &tasks,
+                renderer.as_ref(),
+                sort.unwrap_or(config.get_list_sort()),
+            );
         }
 
         Commands::Add { name, description } => {
-            tasks.add(&name, &description);
+            let mut number = 0;
+            let tasks = tasks::io::update(&config, |tasks| number = tasks.add(&name, &description));
+            let task = found(tasks.find(number));
+
+            report_task(number, task, || println!("{task}"));
+        }
+
+        Commands::Remove { select, force } => {
+            batch_commands(Batch::Remove, select.selection(), force, &config)
         }
 
-        Commands::Remove { number } => {
-            tasks.remove(number);
+        Commands::Toggle { select } => {
+            batch_commands(Batch::Toggle, select.selection(), false, &config)
         }
 
-        Commands::Toggle { number } => {
-            tasks.toggle_completed(number);
+        Commands::Done { select } => {
+            batch_commands(Batch::Done, select.selection(), false, &config)
         }
 
-        Commands::Clean { .. } => {
-            tasks = Tasks::new();
+        Commands::Clean { completed, force } => {
+            batch_commands(Batch::Remove, clean_selection(completed), force, &config)
         }
 
-        _ => {}
+        _ => output::fail(code::INTERNAL, "Not a task command."),
     }
 
-    tasks::io::save(&tasks, &config.get_data_path());
+    if changes {
+        auto_archive(&config);
+    }
+}
+
+/// `clean_selection` returns the selection of `clean`, every task or only the completed ones.
+fn clean_selection(completed: bool) -> Selection {
+    Selection {
+        status: completed.then_some(Status::Completed),
+        ..Selection::default()
+    }
+}
+
+/// `list_renderer` returns the renderer the options of `list` ask for,
+/// falling back to the template of the configuration, then to the long view.
+fn list_renderer(
+    config: &Config,
+    short: bool,
+    format: Option<View>,
+    template: Option<Template>,
+    columns: Vec<Column>,
+    wrap: bool,
+) -> Box<dyn Renderer> {
+    let format = if short { Some(View::Short) } else { format };
+
+    match (format, template) {
+        (None, Some(template)) => Box::new(template),
+        (None, None) => match config.get_list_template() {
+            Some(template) => Box::new(template),
+            None => View::Long.renderer(),
+        },
+        (Some(View::Table), _) => {
+            let columns = if columns.is_empty() {
+                config.get_table_columns()
+            } else {
+                columns
+            };
+            let overflow = if wrap {
+                Overflow::Wrap
+            } else {
+                Overflow::Truncate
+            };
+
+            Box::new(Table::new(columns, terminal_width(), overflow))
+        }
+        (Some(view), _) => view.renderer(),
+    }
+}
+
+/// `print_tasks` writes all the tasks in the given order, with the renderer in the human format.
+fn print_tasks(tasks: &Tasks, renderer: &dyn Renderer, sort: Sort) {
+    let entries = render::entries(tasks, sort);
+    let records: Vec<TaskRecord> = entries
+        .iter()
+        .map(|&(number, task)| TaskRecord::new(number, task))
+        .collect();
+
+    output::report(&json!({ "tasks": records }), || {
+        let text = renderer.render(&entries);
+
+        if !text.is_empty() {
+            println!("{text}");
+        }
+    });
+}
+
+/// `terminal_width` returns the width of the terminal,
+/// or the one in the `COLUMNS` environment variable when the output is not a terminal.
+fn terminal_width() -> Option<usize> {
+    match terminal_size::terminal_size() {
+        Some((Width(width), _)) => Some(width as usize),
+        None => env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok()),
+    }
+}
+
+/// `found` returns the result of an operation on a task,
+/// or exits with NOT_FOUND error code if the task does not exist.
+pub(crate) fn found<T>(result: Result<T, NotFound>) -> T {
+    result.unwrap_or_else(|err| output::fail(code::NOT_FOUND, err))
+}
+
+/// `report_task` writes the task a command changed,
+/// with `human` in the human format and as a task record otherwise.
+fn report_task(number: usize, task: &Task, human: impl FnOnce()) {
+    output::report(&json!({ "tasks": [TaskRecord::new(number, task)] }), human);
+}
+
+/// `parse_error` reports an error of the command line and exits with INVALID_INPUT error code.
+/// The output format is looked up in the raw arguments, since they could not be parsed,
+/// so a structured format still gets the error as such.
+fn parse_error(err: clap::Error) -> ! {
+    let mut args = env::args().skip(1);
+    let mut format = None;
+
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--output") {
+            Some("") => args.next(),
+            Some(rest) => rest.strip_prefix('=').map(String::from),
+            None => None,
+        };
+
+        format = value
+            .and_then(|value| value.parse::<OutputFormat>().ok())
+            .or(format);
+    }
+
+    match format {
+        Some(format) if format != OutputFormat::Human => {
+            output::set_format(format);
+
+            let message = err.render().to_string();
+            let message = message.lines().next().unwrap_or_default();
+            output::fail(code::INVALID_INPUT, message.trim_start_matches("error: "))
+        }
+
+        _ => {
+            let _ = err.print();
+            process::exit(code::INVALID_INPUT);
+        }
+    }
 }
 
 /// `main` is the entry point of the program.
 /// It parses the CLI arguments and calls the appropriate functions.
 /// Refer to the `Cli` struct for more information about parameters.
 ///
-/// To specific functionality refer to the `tasks_commands` and `config_commands` and `convert_commands` functions.
+/// To specific functionality refer to the `tasks_commands`, `batch_commands`, `config_commands`, `lists_commands` and `convert_commands` functions.
 pub fn main() {
-    let cli = Cli::parse();
+    complete_env();
+
+    let cli = Cli::try_parse().unwrap_or_else(|err| {
+        if !err.use_stderr() {
+            err.exit();
+        }
+
+        parse_error(err)
+    });
+
+    output::set_format(cli.globals.output);
+
+    if let Some(path) = &cli.globals.config_path {
+        config::io::use_config_path(path.clone());
+    }
+
+    let settings = config::io::load_lenient(&cli.globals.overrides());
+    output::set_color(cli.globals.color);
+    theme::set_theme(settings.get_theme().clone());
+
+    let script = matches!(
+        cli.command,
+        Commands::Completions { .. } | Commands::Man { .. }
+    );
 
-    println!("{} ", "TODO".green());
+    if output::is_human() && settings.show_banner() && !script {
+        println!("{} ", theme::paint("TODO", &theme::theme().banner));
+    }
 
     match cli.command {
         Commands::Config(command) => {
-            config_commands(command);
+            config_commands(command, &cli.globals);
+        }
+
+        Commands::Init { directory } => init_commands(directory),
+
+        Commands::Lists { command } => lists_commands(command, load_config(&cli.globals)),
+
+        Commands::Move { number, place } => match &place.to {
+            Some(to) => transfer_commands(number, to, Transfer::Move, load_config(&cli.globals)),
+            None => reorder_commands(number, place.place(), load_config(&cli.globals)),
+        },
+
+        Commands::Copy { number, to } => {
+            transfer_commands(number, &to, Transfer::Copy, load_config(&cli.globals))
+        }
+
+        Commands::Archive { select } => {
+            archive_commands(select.selection(), load_config(&cli.globals))
+        }
+
+        Commands::Unarchive { select } => {
+            unarchive_commands(select.selection(), load_config(&cli.globals))
         }
 
-        Commands::Convert { args } => convert_commands(args),
+        Commands::Trash { command } => trash_commands(command, load_config(&cli.globals)),
+
+        Commands::Convert { args } => convert_commands(args, load_config(&cli.globals)),
+
+        Commands::Tui => tui_commands(load_config(&cli.globals)),
+
+        Commands::Shell => shell_commands(&cli.globals),
+
+        Commands::Completions { shell } => completions_commands(&shell),
+
+        Commands::Man { command, out_dir } => man_commands(&command, out_dir.as_deref()),
 
-        _ => tasks_commands(cli.command),
+        Commands::List { .. }
+        | Commands::Add { .. }
+        | Commands::Remove { .. }
+        | Commands::Clean { .. }
+        | Commands::Toggle { .. }
+        | Commands::Done { .. } => tasks_commands(cli.command, load_config(&cli.globals)),
     }
 }
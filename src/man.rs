@@ -0,0 +1,399 @@
+//! A module to handle the manual pages.
+//! There is a page for `todo` and one for every command, like `todo-config-set(1)`,
+//! generated from the command line definition with examples and the exit codes.
+//! Packages can write them all at once with `todo man --out-dir <dir>`,
+//! or from the sources with `cargo xtask man [dir]`, which runs it on a fresh build.
+
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use serde_json::json;
+use todo::libs::output::{self, code};
+
+use crate::Cli;
+
+/// The examples of every command, by its full name.
+const EXAMPLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "todo",
+        &[
+            (
+                "todo add \"Buy milk\" -d \"Two bottles\"",
+                "Add a task with a description.",
+            ),
+            ("todo list --format table", "List the tasks as a table."),
+            (
+                "todo --list work toggle 0",
+                "Complete the first task of the work list.",
+            ),
+            (
+                "todo --output json list",
+                "List the tasks as JSON, for scripts.",
+            ),
+        ],
+    ),
+    (
+        "todo list",
+        &[
+            ("todo list -s", "One line per task."),
+            ("todo list --archived", "The archived tasks."),
+            (
+                "todo list --sort status",
+                "Pending tasks first, in the manual order.",
+            ),
+            (
+                "todo list --format table --columns id,name --wrap",
+                "A table of some columns, wrapping long names.",
+            ),
+            (
+                "todo list -t \"{id:>3} {status} {name}\"",
+                "One line per task from a template.",
+            ),
+        ],
+    ),
+    (
+        "todo add",
+        &[
+            ("todo add \"Write the report\"", "Add a task."),
+            (
+                "todo add \"Call Ana\" -d \"About the trip\"",
+                "Add a task with a description.",
+            ),
+        ],
+    ),
+    (
+        "todo remove",
+        &[
+            (
+                "todo remove 2",
+                "Remove the third task, it can be restored from the trash.",
+            ),
+            (
+                "todo remove --completed --force",
+                "Remove every completed task.",
+            ),
+        ],
+    ),
+    (
+        "todo clean",
+        &[
+            (
+                "todo clean",
+                "Remove all the tasks of the list, once confirmed.",
+            ),
+            (
+                "todo clean --completed --force",
+                "Remove the completed tasks without asking, for scripts.",
+            ),
+        ],
+    ),
+    (
+        "todo toggle",
+        &[
+            ("todo toggle 0", "Complete the first task, or reopen it."),
+            ("todo toggle 0-2", "Toggle the first three tasks."),
+        ],
+    ),
+    (
+        "todo done",
+        &[
+            ("todo done 3 5 7-10", "Complete several tasks at once."),
+            (
+                "todo done --matching report",
+                "Complete the tasks mentioning a report.",
+            ),
+        ],
+    ),
+    (
+        "todo archive",
+        &[
+            ("todo archive", "Move the completed tasks to the archive."),
+            (
+                "todo config set archive_after_days 30",
+                "Archive the tasks completed more than 30 days ago on every list.",
+            ),
+        ],
+    ),
+    (
+        "todo unarchive",
+        &[(
+            "todo unarchive 0",
+            "Bring back the first task of todo list --archived.",
+        )],
+    ),
+    (
+        "todo trash",
+        &[
+            ("todo trash", "Show the removed tasks."),
+            (
+                "todo config set trash_retention_days 7",
+                "Keep the removed tasks for a week.",
+            ),
+        ],
+    ),
+    (
+        "todo trash restore",
+        &[(
+            "todo trash restore 0",
+            "Bring back the first task of todo trash list.",
+        )],
+    ),
+    (
+        "todo trash empty",
+        &[(
+            "todo trash empty --force",
+            "Delete the removed tasks for good, without asking.",
+        )],
+    ),
+    (
+        "todo move",
+        &[
+            (
+                "todo move 1 --to work",
+                "Move the second task to the work list.",
+            ),
+            ("todo move 4 --top", "Put the fifth task first in the list."),
+            (
+                "todo move 4 --after 1",
+                "Put the fifth task right after the second one.",
+            ),
+        ],
+    ),
+    (
+        "todo copy",
+        &[(
+            "todo copy 1 --to ~/shared.todo",
+            "Copy the second task to another data file.",
+        )],
+    ),
+    (
+        "todo tui",
+        &[(
+            "todo tui",
+            "Browse and change the tasks in a full-screen interface.",
+        )],
+    ),
+    (
+        "todo shell",
+        &[("todo --list work shell", "Open a shell over the work list.")],
+    ),
+    (
+        "todo completions",
+        &[
+            (
+                "source <(todo completions bash)",
+                "Enable the completion in the current bash.",
+            ),
+            (
+                "todo completions fish > ~/.config/fish/completions/todo.fish",
+                "Install the completion of fish.",
+            ),
+        ],
+    ),
+    (
+        "todo man",
+        &[
+            ("todo man config set", "Show the page of todo config set."),
+            (
+                "todo man --out-dir /usr/share/man/man1",
+                "Write every page, for packages.",
+            ),
+        ],
+    ),
+    (
+        "todo init",
+        &[
+            ("todo init", "Create a .todo list in the current directory."),
+            ("todo init --directory", "Create a .todo directory instead."),
+        ],
+    ),
+    (
+        "todo lists",
+        &[
+            ("todo lists", "Show the lists with their task counts."),
+            ("todo lists create work", "Create the work list."),
+            (
+                "todo lists default work",
+                "Use the work list when --list is not given.",
+            ),
+        ],
+    ),
+    (
+        "todo lists create",
+        &[(
+            "todo lists create home -p ~/home.todo",
+            "Create a list with its own data file.",
+        )],
+    ),
+    (
+        "todo config get",
+        &[(
+            "todo config get storage",
+            "Show how the changes are written.",
+        )],
+    ),
+    (
+        "todo config set",
+        &[
+            ("todo config set banner false", "Stop printing the banner."),
+            (
+                "todo config set theme.completed \"bright green+bold\"",
+                "Change the color of the completed tasks.",
+            ),
+        ],
+    ),
+    (
+        "todo config unset",
+        &[(
+            "todo config unset theme.completed",
+            "Go back to the default color.",
+        )],
+    ),
+    (
+        "todo config list",
+        &[(
+            "todo config list --origin",
+            "Show every value with the layer it comes from.",
+        )],
+    ),
+    (
+        "todo config check",
+        &[(
+            "todo config check",
+            "Report every problem of the configuration.",
+        )],
+    ),
+    (
+        "todo config data-path",
+        &[(
+            "todo config data-path ~/todo.toml",
+            "Keep the tasks in another file.",
+        )],
+    ),
+    (
+        "todo config storage",
+        &[(
+            "todo config storage event-log --compact-after 100",
+            "Append the changes to a log, compacted every 100 changes.",
+        )],
+    ),
+    (
+        "todo convert",
+        &[
+            (
+                "todo convert export json tasks.json",
+                "Write the tasks as JSON.",
+            ),
+            (
+                "todo convert import yaml tasks.yaml",
+                "Replace the tasks with the ones of a YAML file.",
+            ),
+        ],
+    ),
+];
+
+/// `man_commands` writes the page of the given command, or of `todo` if none is given,
+/// or writes every page to a directory.
+pub fn man_commands(command: &[String], out_dir: Option<&Path>) {
+    let mut root = Cli::command().disable_help_subcommand(true);
+    root.build();
+
+    if let Some(dir) = out_dir {
+        let mut written = vec![];
+
+        if let Err(err) = write_pages(&root, dir, &mut written) {
+            output::fail(code::of_io(&err), format!("Man pages, {err}"));
+        }
+
+        return output::report(&json!({ "pages": written }), || {
+            for page in &written {
+                println!("{page}");
+            }
+        });
+    }
+
+    let mut page = &root;
+
+    for name in command {
+        page = match page.find_subcommand(name) {
+            Some(subcommand) => subcommand,
+            None => output::fail(
+                code::INVALID_INPUT,
+                format!("Unknown command {:?}.", command.join(" ")),
+            ),
+        };
+    }
+
+    match render(page, &mut io::stdout()) {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => {
+            output::fail(code::of_io(&err), format!("Man page, {err}"));
+        }
+        _ => {}
+    }
+}
+
+/// `write_pages` writes the page of a command and of all its commands to a directory,
+/// adding the paths of the written pages.
+fn write_pages(command: &Command, dir: &Path, written: &mut Vec<String>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let path = dir.join(Man::new(command.clone()).get_filename());
+    let mut file = fs::File::create(&path)?;
+    render(command, &mut file)?;
+    written.push(path.display().to_string());
+
+    for subcommand in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        write_pages(subcommand, dir, written)?;
+    }
+
+    Ok(())
+}
+
+/// `render` writes the page of a command as roff,
+/// the page of clap followed by the examples and the exit codes.
+fn render(command: &Command, out: &mut dyn Write) -> io::Result<()> {
+    let name = command.get_bin_name().unwrap_or(command.get_name());
+
+    Man::new(command.clone())
+        .source(format!("todo {}", env!("CARGO_PKG_VERSION")))
+        .manual("Todo Manual")
+        .render(out)?;
+
+    if let Some((_, examples)) = EXAMPLES.iter().find(|(example, _)| *example == name) {
+        writeln!(out, ".SH EXAMPLES")?;
+
+        for (line, description) in examples.iter() {
+            writeln!(
+                out,
+                ".TP\n\\fB{}\\fR\n{}",
+                escape(line),
+                escape(description)
+            )?;
+        }
+    }
+
+    writeln!(out, ".SH \"EXIT STATUS\"")?;
+
+    for (code, kind, description) in code::ALL {
+        writeln!(out, ".TP\n\\fB{code}\\fR ({kind})\n{}", escape(description))?;
+    }
+
+    if command.get_version().is_none() {
+        writeln!(out, ".SH \"SEE ALSO\"\n\\fBtodo\\fR(1)")?;
+    }
+
+    Ok(())
+}
+
+/// `escape` escapes a text for roff, so it is shown as it is.
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+
+    match text.chars().next() {
+        Some('.' | '\'') => format!("\\&{text}"),
+        _ => text,
+    }
+}
@@ -0,0 +1,428 @@
+//! A module to handle the interactive shell.
+//! It keeps the tasks of the list in use in memory and runs the same commands
+//! as the command line without the `todo` prefix, saving after every change.
+//! Commands that work on other lists or on the configuration are run as a separate `todo`.
+
+use std::path::PathBuf;
+use std::{env, process};
+
+use clap::{CommandFactory, Parser};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Context, Editor, Helper};
+use todo::libs::config::Config;
+use todo::libs::output::code;
+use todo::libs::tasks::archive;
+use todo::libs::tasks::selection::{Selection, Target};
+use todo::libs::tasks::Tasks;
+use todo::libs::{config, output, tasks};
+
+use crate::batch::{self, Batch};
+use crate::transfer;
+use crate::{
+    clean_selection, list_renderer, load_config, print_tasks, report_task, Commands, GlobalArgs,
+};
+
+/// The name of the history file, next to the user config file.
+const HISTORY_FILE_NAME: &str = "todo.history";
+
+/// The commands of the shell itself.
+const BUILTINS: [&str; 4] = ["help", "reload", "exit", "quit"];
+
+/// The commands that take a task number first.
+const NUMBERED: [&str; 5] = ["remove", "toggle", "done", "move", "copy"];
+
+/// The commands that take any number of task numbers.
+const BATCH: [&str; 3] = ["remove", "toggle", "done"];
+
+// `Line` is a line typed in the shell, a command without the `todo` prefix.
+#[derive(Parser)]
+#[command(
+    no_binary_name = true,
+    disable_version_flag = true,
+    about = "Type the commands of todo without the todo prefix, reload to read the list again, exit or quit to leave"
+)]
+struct Line {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// `Shell` is the state of the shell.
+struct Shell<'a> {
+    globals: &'a GlobalArgs,
+    config: Config,
+    tasks: Tasks,
+    /// The tasks as they were last read or written, to tell if another process changed them.
+    loaded: Tasks,
+}
+
+/// `shell_commands` runs the shell over the list in use until `exit` or the end of the input.
+pub fn shell_commands(globals: &GlobalArgs) {
+    let config = load_config(globals);
+    let tasks = tasks::io::load(&config.get_data_path());
+    let mut shell = Shell {
+        globals,
+        config,
+        loaded: tasks.clone(),
+        tasks,
+    };
+
+    let settings = rustyline::Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+
+    let mut editor: Editor<Completion, DefaultHistory> = match Editor::with_config(settings) {
+        Ok(editor) => editor,
+        Err(err) => output::fail(code::IO_ERROR, format!("Terminal, {err}")),
+    };
+
+    editor.set_helper(Some(Completion::new(&shell)));
+
+    let history = history_path();
+    let _ = editor.load_history(&history);
+
+    if output::is_human() {
+        println!("Type help for the commands, exit to leave.");
+    }
+
+    loop {
+        let line = match editor.readline("todo> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => output::fail(code::IO_ERROR, format!("Terminal, {err}")),
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let _ = editor.add_history_entry(line.as_str());
+
+        if !shell.run(&line) {
+            break;
+        }
+
+        editor.set_helper(Some(Completion::new(&shell)));
+    }
+
+    if let Err(err) = editor.save_history(&history) {
+        output::error(code::IO_ERROR, format!("History file, {err}"));
+    }
+}
+
+/// `history_path` returns the path of the history file.
+fn history_path() -> PathBuf {
+    config::io::config_path().with_file_name(HISTORY_FILE_NAME)
+}
+
+/// `Shell` is an implementation of the shell.
+impl Shell<'_> {
+    /// `run` runs a line typed in the shell.
+    /// Returns false once the shell has to be left.
+    fn run(&mut self, line: &str) -> bool {
+        let words = match split_words(line) {
+            Ok(words) => words,
+            Err(message) => {
+                output::error(code::INVALID_INPUT, message);
+                return true;
+            }
+        };
+
+        let Some(first) = words.first() else {
+            return true;
+        };
+
+        match first.as_str() {
+            "exit" | "quit" => return false,
+            "reload" => {
+                self.reload();
+                return true;
+            }
+            _ => {}
+        }
+
+        match Line::try_parse_from(&words) {
+            Ok(line) => self.execute(line.command, &words),
+            Err(err) => {
+                let _ = err.print();
+            }
+        }
+
+        true
+    }
+
+    /// `execute` runs a command, over the tasks in memory when it only changes them.
+    fn execute(&mut self, command: Commands, words: &[String]) {
+        match command {
+            Commands::List {
+                short,
+                format,
+                template,
+                columns,
+                wrap,
+                archived,
+                sort,
+            } => {
+                let renderer = list_renderer(&self.config, short, format, template, columns, wrap);
+                let sort = sort.unwrap_or(self.config.get_list_sort());
+
+                if archived {
+                    print_tasks(&archive::load(&self.config), renderer.as_ref(), sort);
+                } else {
+                    print_tasks(&self.tasks, renderer.as_ref(), sort);
+                }
+            }
+
+            Commands::Add { name, description } => {
+                let number = self.tasks.add(&name, &description);
+
+                if let (true, Some(task)) = (self.save(), self.tasks.get(number)) {
+                    report_task(number, task, || println!("{task}"));
+                }
+            }
+
+            Commands::Remove { select, force } => {
+                self.batch(Batch::Remove, select.selection(), force)
+            }
+
+            Commands::Toggle { select } => self.batch(Batch::Toggle, select.selection(), false),
+
+            Commands::Done { select } => self.batch(Batch::Done, select.selection(), false),
+
+            Commands::Clean { completed, force } => {
+                self.batch(Batch::Remove, clean_selection(completed), force)
+            }
+
+            Commands::Move { number, place } if place.to.is_none() => {
+                match transfer::reorder(&mut self.tasks, number, place.place()) {
+                    Ok((to, task)) => {
+                        if self.save() {
+                            transfer::report_reorder(number, to, &task);
+                        }
+                    }
+                    Err((code, message)) => output::error(code, message),
+                }
+            }
+
+            Commands::Shell => output::error(code::INVALID_INPUT, "Already in the shell."),
+
+            _ => self.delegate(words),
+        }
+    }
+
+    /// `batch` makes a change to the selected tasks in memory and saves them,
+    /// writing an error instead if the change cannot be made.
+    fn batch(&mut self, batch: Batch, selection: Selection, force: bool) {
+        match batch::apply(&mut self.tasks, batch, &selection, force) {
+            Ok(outcome) => {
+                if self.save() {
+                    batch::report(&outcome);
+                }
+            }
+            Err((code, message)) => output::error(code, message),
+        }
+    }
+
+    /// `save` saves the tasks in memory, holding the lock of the data file.
+    /// If they cannot be saved, or another process changed them since they were read,
+    /// the error is written and the tasks are read again.
+    fn save(&mut self) -> bool {
+        match tasks::io::try_save(&self.config, &mut self.tasks, &self.loaded) {
+            Ok(true) => {
+                self.loaded = self.tasks.clone();
+                true
+            }
+            Ok(false) => {
+                output::error(
+                    code::CONFLICT,
+                    "The list was changed by another process, nothing was saved, run the command again.",
+                );
+                self.reload();
+                false
+            }
+            Err(err) => {
+                output::error(code::of_io(&err), format!("Data file, {err}"));
+                self.reload();
+                false
+            }
+        }
+    }
+
+    /// `reload` reads the configuration and the tasks again.
+    fn reload(&mut self) {
+        self.config = load_config(self.globals);
+
+        match tasks::io::store(&self.config, self.config.get_data_path()).load() {
+            Ok(tasks) => {
+                self.loaded = tasks.clone();
+                self.tasks = tasks;
+            }
+            Err(err) => output::error(code::of_io(&err), format!("Data file, {err}")),
+        }
+    }
+
+    /// `delegate` runs a command as a separate `todo` with the same global options,
+    /// then reads the configuration and the tasks again, as it may have changed them.
+    fn delegate(&mut self, words: &[String]) {
+        let color = if colored::control::SHOULD_COLORIZE.should_colorize() {
+            "always"
+        } else {
+            "never"
+        };
+
+        let status = env::current_exe().and_then(|program| {
+            process::Command::new(program)
+                .args(self.globals.to_args())
+                .args(["-c", "banner=false", "--color", color])
+                .args(words)
+                .status()
+        });
+
+        if let Err(err) = status {
+            output::error(code::of_io(&err), format!("Running {}, {err}", words[0]));
+        }
+
+        self.reload();
+    }
+}
+
+/// `split_words` splits a line into words like a shell does,
+/// keeping text in single or double quotes together and taking `\` to escape a character.
+/// Returns a message if a quote is not closed.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(char) = chars.next() {
+        match (quote, char) {
+            (Some(open), char) if char == open => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            (Some(_), char) => word.get_or_insert_with(String::new).push(char),
+            (None, '"' | '\'') => {
+                quote = Some(char);
+                word.get_or_insert_with(String::new);
+            }
+            (None, char) if char.is_whitespace() => words.extend(word.take()),
+            (None, char) => word.get_or_insert_with(String::new).push(char),
+        }
+    }
+
+    if let Some(open) = quote {
+        return Err(format!("{open} is not closed"));
+    }
+
+    words.extend(word);
+    Ok(words)
+}
+
+/// `Completion` completes the commands, the task numbers and the list names in the shell.
+struct Completion {
+    commands: Vec<String>,
+    numbers: Vec<Pair>,
+    lists: Vec<String>,
+}
+
+/// `Completion` is an implementation of the completion.
+impl Completion {
+    /// `new` creates the completion for the current state of the shell.
+    fn new(shell: &Shell) -> Completion {
+        let mut commands: Vec<String> = Line::command()
+            .get_subcommands()
+            .map(|command| command.get_name().to_string())
+            .chain(BUILTINS.map(String::from))
+            .collect();
+        commands.sort();
+
+        let numbers = shell
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(number, task)| Pair {
+                display: format!("{number} - {}", task.name),
+                replacement: number.to_string(),
+            })
+            .collect();
+
+        Completion {
+            commands,
+            numbers,
+            lists: shell.config.lists(),
+        }
+    }
+}
+
+/// `Completer` is an implementation to complete the word under the cursor.
+impl Completer for Completion {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map_or(0, |space| space + 1);
+        let word = &line[start..pos];
+        let before: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let names = |names: &[String]| -> Vec<Pair> {
+            names
+                .iter()
+                .filter(|name| name.starts_with(word))
+                .map(|name| Pair {
+                    display: name.clone(),
+                    replacement: name.clone(),
+                })
+                .collect()
+        };
+
+        let candidates = match (before.first(), before.last()) {
+            (None, _) => names(&self.commands),
+            (_, Some(&("--list" | "--to"))) => names(&self.lists),
+            (Some(command), Some(last))
+                if NUMBERED.contains(command)
+                    && (command == last
+                        || BATCH.contains(command) && last.parse::<Target>().is_ok()) =>
+            {
+                self.numbers
+                    .iter()
+                    .filter(|pair| pair.replacement.starts_with(word))
+                    .map(|pair| Pair {
+                        display: pair.display.clone(),
+                        replacement: pair.replacement.clone(),
+                    })
+                    .collect()
+            }
+            _ => vec![],
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+/// `Hinter` is an implementation without hints.
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+/// `Highlighter` is an implementation without highlighting.
+impl Highlighter for Completion {}
+
+/// `Validator` is an implementation accepting every line.
+impl Validator for Completion {}
+
+/// `Helper` is an implementation to use the completion in the editor.
+impl Helper for Completion {}
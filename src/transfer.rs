@@ -0,0 +1,173 @@
+//! A module to handle moving and copying tasks
+//! between lists and data files, and moving them within the list in use.
+//! The order of the list is the manual order, kept in the data file like any other change.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use clap_complete::engine::ArgValueCandidates;
+use serde_json::json;
+use todo::libs::config::Config;
+use todo::libs::output::{code, TaskRecord};
+use todo::libs::tasks::order::Place;
+use todo::libs::tasks::task::Task;
+use todo::libs::tasks::Tasks;
+use todo::libs::{output, tasks};
+
+use crate::batch::not_found;
+use crate::completions::{list_names, task_numbers};
+use crate::found;
+
+#[derive(Args)]
+#[group(id = "place", required = true, multiple = false)]
+pub struct PlaceArgs {
+    /// The name of the list, or the path of the data file, to move the task to
+    #[arg(long, add = ArgValueCandidates::new(list_names))]
+    pub to: Option<String>,
+
+    /// Put the task right before this one in the list
+    #[arg(long, value_name = "NUMBER", add = ArgValueCandidates::new(task_numbers))]
+    pub before: Option<usize>,
+
+    /// Put the task right after this one in the list
+    #[arg(long, value_name = "NUMBER", add = ArgValueCandidates::new(task_numbers))]
+    pub after: Option<usize>,
+
+    /// Put the task first in the list
+    #[arg(long)]
+    pub top: bool,
+
+    /// Put the task last in the list
+    #[arg(long)]
+    pub bottom: bool,
+}
+
+/// `PlaceArgs` is an implementation of the place options.
+impl PlaceArgs {
+    /// `place` returns the place in the list the options describe,
+    /// meaningful when the task is not moved to another list with `to`.
+    pub fn place(&self) -> Place {
+        match (self.before, self.after, self.top) {
+            (Some(anchor), ..) => Place::Before(anchor),
+            (_, Some(anchor), _) => Place::After(anchor),
+            (_, _, true) => Place::Top,
+            _ => Place::Bottom,
+        }
+    }
+}
+
+/// `Transfer` is what happens to the task in the source list.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Transfer {
+    /// The task is removed from the source list.
+    Move,
+    /// The task is kept in the source list.
+    Copy,
+}
+
+/// `resolve_target` returns the data path of a target,
+/// which is a list name if such a list exists, or a path otherwise.
+fn resolve_target(config: &Config, target: &str) -> PathBuf {
+    if config.has_list(target) {
+        config.get_list_path(target)
+    } else {
+        PathBuf::from(target)
+    }
+}
+
+/// `same_file` checks if two paths point to the same data file.
+fn same_file(first: &Path, second: &Path) -> bool {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    canonical(first) == canonical(second)
+}
+
+/// `transfer_commands` moves or copies a task, with all its fields,
+/// from the list in use to another list or data file.
+///
+/// Both data files are locked for the whole operation.
+/// The task is added to the target before it is removed from the source,
+/// so a failure midway never loses it.
+///
+/// # Example
+///
+/// ```
+/// transfer_commands(0, "work", Transfer::Move, config::io::load());
+/// ```
+pub(crate) fn transfer_commands(number: usize, to: &str, transfer: Transfer, config: Config) {
+    let source = config.get_data_path();
+    let target = resolve_target(&config, to);
+
+    if same_file(&source, &target) {
+        output::fail(
+            code::INVALID_INPUT,
+            "Source and target are the same data file.",
+        );
+    }
+
+    let _locks = tasks::io::lock(&[&source, &target]);
+
+    let task = found(tasks::io::load(&source).find(number)).clone();
+
+    tasks::io::update_locked(&config, target.clone(), |tasks| tasks.push(task.clone()));
+
+    if transfer == Transfer::Move {
+        tasks::io::update_locked(&config, source, |tasks| {
+            found(tasks.take(number));
+        });
+    }
+
+    let record = TaskRecord::new(number, &task);
+
+    output::report(&json!({ "tasks": [record], "target": target }), || {
+        println!("{number} - {} -> {:?}", task.to_string_short(), target)
+    });
+}
+
+/// `reorder` moves a task to another place in the list and returns it with its new number.
+/// Fails with NOT_FOUND if a task is missing,
+/// and with INVALID_INPUT if the place is relative to the task itself.
+pub fn reorder(
+    tasks: &mut Tasks,
+    number: usize,
+    place: Place,
+) -> Result<(usize, Task), (i32, String)> {
+    let to = tasks.reorder(number, place).map_err(not_found)?.ok_or((
+        code::INVALID_INPUT,
+        String::from("A task cannot be moved before or after itself."),
+    ))?;
+
+    let task = tasks.find(to).map_err(not_found)?.clone();
+
+    Ok((to, task))
+}
+
+/// `report_reorder` writes the task moved within the list, with its old and new numbers.
+pub fn report_reorder(from: usize, to: usize, task: &Task) {
+    output::report(
+        &json!({ "tasks": [TaskRecord::new(to, task)], "from": from }),
+        || println!("{from} -> {to} - {}", task.to_string_short()),
+    );
+}
+
+/// `reorder_commands` moves a task to another place in the list in use,
+/// shifting the tasks in between.
+///
+/// # Example
+///
+/// ```
+/// reorder_commands(5, Place::Top, config::io::load());
+/// ```
+pub(crate) fn reorder_commands(number: usize, place: Place, config: Config) {
+    let mut moved = None;
+
+    tasks::io::update(&config, |tasks| match reorder(tasks, number, place) {
+        Ok(done) => moved = Some(done),
+        Err((code, message)) => output::fail(code, message),
+    });
+
+    if let Some((to, task)) = moved {
+        report_reorder(number, to, &task);
+    }
+}
@@ -0,0 +1,155 @@
+//! A module to handle the trash of the list in use
+//! from the command line.
+
+use clap::Subcommand;
+use clap_complete::engine::ArgValueCandidates;
+use serde::Serialize;
+use serde_json::json;
+use todo::libs::config::Config;
+use todo::libs::output;
+use todo::libs::output::{code, TaskRecord};
+use todo::libs::tasks::trash::{self, Trash};
+use todo::libs::tasks::{self, DAY};
+
+use crate::batch::{choose, count_tasks, report_moved, SelectArgs};
+use crate::completions::trashed_numbers;
+
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    /// List the removed tasks, the last removed at the end
+    List,
+
+    /// Move removed tasks back to the list, the numbers are the ones of trash list
+    #[command(mut_arg("targets", |arg| arg.add(ArgValueCandidates::new(trashed_numbers))))]
+    Restore {
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+
+    /// Delete the removed tasks for good
+    Empty {
+        /// Delete without asking, needed to delete more than one task from scripts
+        #[arg(long, short, visible_alias = "yes", short_alias = 'y')]
+        force: bool,
+    },
+}
+
+/// `TrashRecord` is a removed task as written in the structured formats.
+#[derive(Serialize)]
+struct TrashRecord {
+    #[serde(flatten)]
+    task: TaskRecord,
+    removed_at: u64,
+}
+
+/// `trash_commands` is a convenience function for managing the trash of the list in use.
+/// Without a command, it lists the removed tasks.
+///
+/// Refer to the `TrashCommands` struct for more information about parameters.
+///
+/// # Example
+///
+/// ```
+/// trash_commands(Some(TrashCommands::Empty { force: true }), config::io::load());
+/// ```
+pub(crate) fn trash_commands(command: Option<TrashCommands>, config: Config) {
+    match command.unwrap_or(TrashCommands::List) {
+        TrashCommands::List => list_trash(&trash::load(&config)),
+
+        TrashCommands::Restore { select } => {
+            let selection = select.selection();
+            let restored: Vec<_> =
+                trash::restore(&config, |tasks| choose(tasks, &selection, "Removed task"))
+                    .into_iter()
+                    .map(|(number, trashed)| (number, trashed.task))
+                    .collect();
+
+            report_moved(&restored, "Restored");
+        }
+
+        TrashCommands::Empty { force } => empty_trash(&config, force),
+    }
+}
+
+/// `list_trash` writes the removed tasks with their numbers and how long ago they were removed.
+fn list_trash(trash: &Trash) {
+    let records: Vec<TrashRecord> = trash
+        .iter()
+        .enumerate()
+        .map(|(number, trashed)| TrashRecord {
+            task: TaskRecord::new(number, &trashed.task),
+            removed_at: trashed.removed_at,
+        })
+        .collect();
+
+    output::report(&json!({ "tasks": records }), || {
+        if trash.is_empty() {
+            return println!("The trash is empty.");
+        }
+
+        for (number, trashed) in trash.iter().enumerate() {
+            println!(
+                "{number} - {} - removed {}",
+                trashed.task.to_string_short(),
+                ago(trashed.removed_at)
+            );
+        }
+    });
+}
+
+/// `empty_trash` deletes the removed tasks for good,
+/// once confirmed on the terminal unless `force` is given.
+/// Without a terminal to ask on, deleting more than one task needs `force`.
+fn empty_trash(config: &Config, force: bool) {
+    let mut cancelled = false;
+
+    let deleted = trash::empty(config, |trash| {
+        if force {
+            return true;
+        }
+
+        let lines: Vec<String> = trash
+            .iter()
+            .enumerate()
+            .map(|(number, trashed)| format!("{number} - {}", trashed.task.to_string_short()))
+            .collect();
+
+        let question = format!("Delete {} for good?", count_tasks(trash.len()));
+
+        match output::confirm(&lines, &question) {
+            Some(answer) => {
+                cancelled = !answer;
+                answer
+            }
+            None if trash.len() > 1 => output::fail(
+                code::INVALID_INPUT,
+                format!(
+                    "This deletes {} for good, add --force to confirm.",
+                    count_tasks(trash.len())
+                ),
+            ),
+            None => true,
+        }
+    });
+
+    let count = deleted.as_ref().map_or(0, Trash::len);
+
+    output::report(&json!({ "deleted": count, "cancelled": cancelled }), || {
+        if cancelled {
+            println!("Cancelled, nothing was changed.");
+        } else if count == 0 {
+            println!("The trash is empty.");
+        } else {
+            println!("Deleted {} for good.", count_tasks(count));
+        }
+    });
+}
+
+/// `ago` returns how long ago a time was, in days.
+fn ago(at: u64) -> String {
+    match tasks::now().saturating_sub(at) / DAY {
+        0 => String::from("today"),
+        1 => String::from("yesterday"),
+        days => format!("{days} days ago"),
+    }
+}
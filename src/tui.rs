@@ -0,0 +1,519 @@
+//! A module to handle the full-screen interactive interface.
+//! The tasks of the list in use are shown one per line, to navigate with the keys,
+//! and every change is saved right away through `tasks::io`, holding the lock of the data file.
+
+use std::io::{self, IsTerminal, Stdout, Write};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use todo::libs::config::Config;
+use todo::libs::output::code;
+use todo::libs::render::theme::{self, paint};
+use todo::libs::tasks::order::Sort;
+use todo::libs::tasks::task::Task;
+use todo::libs::tasks::{NotFound, Tasks};
+use todo::libs::{output, tasks};
+
+/// The keys shown at the bottom of the screen.
+const HELP: &str =
+    "↑↓ move  space toggle  a add  e edit  d delete  / filter  s sort  r reload  q quit";
+
+/// `next_sort` returns the order that follows the given one, when `s` is pressed.
+fn next_sort(sort: Sort) -> Sort {
+    match sort {
+        Sort::Manual => Sort::Name,
+        Sort::Name => Sort::Status,
+        Sort::Status => Sort::Manual,
+    }
+}
+
+/// `Prompt` is the text being asked for at the bottom of the screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Prompt {
+    /// The name of a new task.
+    AddName,
+    /// The description of a new task with the given name.
+    AddDescription { name: String },
+    /// The new name of a task.
+    EditName { number: usize },
+    /// The new description of a task, renamed to the given name.
+    EditDescription { number: usize, name: String },
+}
+
+/// `Prompt` is an implementation of the prompt.
+impl Prompt {
+    /// `label` returns the question shown before the text.
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::AddName | Prompt::EditName { .. } => "Name",
+            Prompt::AddDescription { .. } | Prompt::EditDescription { .. } => "Description",
+        }
+    }
+}
+
+/// `Mode` is what the keys do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    /// The keys move through the tasks and change them.
+    Normal,
+    /// The keys type the filter, the tasks are filtered as it is typed.
+    Filter,
+    /// The keys type the answer of a prompt.
+    Input { prompt: Prompt, text: String },
+    /// The removal of a task waits to be confirmed.
+    Confirm { number: usize },
+}
+
+/// `App` is the state of the interface.
+struct App {
+    config: Config,
+    tasks: Tasks,
+    selected: usize,
+    offset: usize,
+    filter: String,
+    sort: Sort,
+    mode: Mode,
+    message: Option<String>,
+    quit: bool,
+}
+
+/// `Screen` holds the terminal in raw mode on the alternate screen,
+/// and gives it back as it was when dropped.
+struct Screen {
+    out: Stdout,
+}
+
+/// `Screen` is an implementation of the screen.
+impl Screen {
+    /// `enter` switches the terminal to the interface.
+    fn enter() -> io::Result<Screen> {
+        let mut out = io::stdout();
+
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+
+        Ok(Screen { out })
+    }
+}
+
+/// `Drop` is an implementation to give the terminal back.
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// `tui_commands` runs the interactive interface over the list in use until it is quit.
+/// If the output is not a terminal, the program will exit.
+pub fn tui_commands(config: Config) {
+    if !output::is_human() || !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        output::fail(
+            code::INVALID_INPUT,
+            "The interface needs a terminal, run it without --output and outside of a pipe.",
+        );
+    }
+
+    let tasks = tasks::io::load(&config.get_data_path());
+    let mut app = App::new(config, tasks);
+
+    if let Err(err) = run(&mut app) {
+        output::fail(code::IO_ERROR, format!("Terminal, {err}"));
+    }
+}
+
+/// `run` draws the interface and handles the keys until it is quit.
+fn run(app: &mut App) -> io::Result<()> {
+    let mut screen = Screen::enter()?;
+
+    while !app.quit {
+        app.draw(&mut screen.out)?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle(key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `App` is an implementation of the interface.
+impl App {
+    /// `new` creates the interface over the given tasks.
+    fn new(config: Config, tasks: Tasks) -> App {
+        App {
+            sort: config.get_list_sort(),
+            config,
+            tasks,
+            selected: 0,
+            offset: 0,
+            filter: String::new(),
+            mode: Mode::Normal,
+            message: None,
+            quit: false,
+        }
+    }
+
+    /// `visible` returns the numbers of the tasks that match the filter, in the chosen order.
+    fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        let mut numbers: Vec<usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| {
+                task.name.to_lowercase().contains(&filter)
+                    || task.description.to_lowercase().contains(&filter)
+            })
+            .map(|(number, _)| number)
+            .collect();
+
+        self.sort.sort(&self.tasks, &mut numbers);
+        numbers
+    }
+
+    /// `current` returns the number of the selected task, if any.
+    fn current(&self) -> Option<usize> {
+        self.visible().get(self.selected).copied()
+    }
+
+    /// `select` selects the task with the given number, if it is visible.
+    fn select(&mut self, number: usize) {
+        if let Some(position) = self.visible().iter().position(|visible| *visible == number) {
+            self.selected = position;
+        }
+    }
+
+    /// `change` applies a change to the tasks and saves it, holding the lock of the data file.
+    /// The tasks are reloaded with the saved ones, so changes made elsewhere show up too.
+    fn change(&mut self, change: impl FnMut(&mut Tasks)) -> bool {
+        match tasks::io::try_update(&self.config, change) {
+            Ok(tasks) => {
+                self.tasks = tasks;
+                true
+            }
+            Err(err) => {
+                self.message = Some(format!("Data file, {err}"));
+                false
+            }
+        }
+    }
+
+    /// `change_task` applies a change to the task with the given number like `change`,
+    /// once checked that the saved list still has the task shown under this number.
+    /// If it was changed, moved or removed elsewhere, nothing is changed,
+    /// the saved tasks are shown instead and false is returned.
+    fn change_task(
+        &mut self,
+        number: usize,
+        mut change: impl FnMut(&mut Tasks) -> Result<(), NotFound>,
+    ) -> bool {
+        let Some(shown) = self.tasks.get(number).cloned() else {
+            return false;
+        };
+
+        let mut stale = false;
+
+        let changed = self.change(|tasks| {
+            stale = !tasks.get(number).is_some_and(|task| same(task, &shown));
+
+            if !stale {
+                stale = change(tasks).is_err();
+            }
+        });
+
+        if changed && stale {
+            self.message = Some(format!(
+                "Task {number} was changed elsewhere, nothing was done, check it and try again."
+            ));
+        }
+
+        changed && !stale
+    }
+
+    /// `reload` reads the tasks again from the data file.
+    fn reload(&mut self) {
+        match tasks::io::store(&self.config, self.config.get_data_path()).load() {
+            Ok(tasks) => {
+                self.tasks = tasks;
+                self.message = Some(String::from("Reloaded."));
+            }
+            Err(err) => self.message = Some(format!("Data file, {err}")),
+        }
+    }
+
+    /// `handle` does what the key means in the current mode.
+    fn handle(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal(key),
+            Mode::Filter => self.handle_filter(key),
+            Mode::Input { prompt, text } => self.handle_input(key, prompt, text),
+            Mode::Confirm { number } => self.handle_confirm(key, number),
+        }
+
+        let count = self.visible().len();
+        self.selected = self.selected.min(count.saturating_sub(1));
+    }
+
+    /// `handle_normal` moves through the tasks or starts a change.
+    fn handle_normal(&mut self, key: KeyEvent) {
+        self.message = None;
+        let count = self.visible().len();
+
+        match key.code {
+            KeyCode::Esc if !self.filter.is_empty() => self.filter.clear(),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(count.saturating_sub(1))
+            }
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::PageDown => self.selected = (self.selected + 10).min(count.saturating_sub(1)),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = count.saturating_sub(1),
+
+            KeyCode::Char(' ' | 'x') | KeyCode::Enter => {
+                if let Some(number) = self.current() {
+                    self.change_task(number, |tasks| tasks.toggle_completed(number).map(|_| ()));
+                }
+            }
+
+            KeyCode::Char('a') => {
+                self.mode = Mode::Input {
+                    prompt: Prompt::AddName,
+                    text: String::new(),
+                }
+            }
+
+            KeyCode::Char('e') => {
+                if let Some((number, task)) = self
+                    .current()
+                    .and_then(|number| self.tasks.get(number).map(|task| (number, task)))
+                {
+                    self.mode = Mode::Input {
+                        prompt: Prompt::EditName { number },
+                        text: task.name.clone(),
+                    }
+                }
+            }
+
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(number) = self.current() {
+                    self.mode = Mode::Confirm { number }
+                }
+            }
+
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('s') => self.sort = next_sort(self.sort),
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+    }
+
+    /// `handle_filter` types the filter, Enter keeps it and Esc clears it.
+    fn handle_filter(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {}
+            KeyCode::Esc => self.filter.clear(),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.mode = Mode::Filter;
+            }
+            KeyCode::Char(char) => {
+                self.filter.push(char);
+                self.selected = 0;
+                self.mode = Mode::Filter;
+            }
+            _ => self.mode = Mode::Filter,
+        }
+    }
+
+    /// `handle_input` types the answer of a prompt, Enter submits it and Esc cancels it.
+    fn handle_input(&mut self, key: KeyEvent, prompt: Prompt, mut text: String) {
+        match key.code {
+            KeyCode::Esc => self.message = Some(String::from("Cancelled.")),
+            KeyCode::Enter => self.submit(prompt, text),
+            KeyCode::Backspace => {
+                text.pop();
+                self.mode = Mode::Input { prompt, text };
+            }
+            KeyCode::Char(char) => {
+                text.push(char);
+                self.mode = Mode::Input { prompt, text };
+            }
+            _ => self.mode = Mode::Input { prompt, text },
+        }
+    }
+
+    /// `submit` goes on with the answer of a prompt, saving the change once it is complete.
+    fn submit(&mut self, prompt: Prompt, text: String) {
+        match prompt {
+            Prompt::AddName | Prompt::EditName { .. } if text.trim().is_empty() => {
+                self.message = Some(String::from("A task needs a name."));
+            }
+
+            Prompt::AddName => {
+                self.mode = Mode::Input {
+                    prompt: Prompt::AddDescription { name: text },
+                    text: String::new(),
+                }
+            }
+
+            Prompt::AddDescription { name } => {
+                let description = Some(text);
+                let mut number = 0;
+
+                if self.change(|tasks| number = tasks.add(&name, &description)) {
+                    self.select(number);
+                    self.message = Some(format!("Added task {number}."));
+                }
+            }
+
+            Prompt::EditName { number } => {
+                self.mode = Mode::Input {
+                    prompt: Prompt::EditDescription { number, name: text },
+                    text: self
+                        .tasks
+                        .get(number)
+                        .map(|task| task.description.clone())
+                        .unwrap_or_default(),
+                }
+            }
+
+            Prompt::EditDescription { number, name } => {
+                let (name, description) = (Some(name), Some(text));
+
+                if self.change_task(number, |tasks| {
+                    tasks.edit(number, name.clone(), description.clone())
+                }) {
+                    self.message = Some(format!("Edited task {number}."));
+                }
+            }
+        }
+    }
+
+    /// `handle_confirm` removes the task on `y`, any other key keeps it.
+    fn handle_confirm(&mut self, key: KeyEvent, number: usize) {
+        if key.code != KeyCode::Char('y') {
+            self.message = Some(String::from("Cancelled."));
+            return;
+        }
+
+        if self.change_task(number, |tasks| tasks.remove(number).map(|_| ())) {
+            self.message = Some(format!("Removed task {number}."));
+        }
+    }
+
+    /// `draw` draws the whole interface.
+    fn draw(&mut self, out: &mut Stdout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let rows = height.saturating_sub(3);
+        let visible = self.visible();
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if rows > 0 && self.selected >= self.offset + rows {
+            self.offset = self.selected + 1 - rows;
+        }
+
+        let mut title = format!(
+            "{} of {} tasks, sorted by {}",
+            visible.len(),
+            self.tasks.len(),
+            self.sort.label()
+        );
+
+        if !self.filter.is_empty() {
+            title.push_str(&format!(", filtered by {:?}", self.filter));
+        }
+
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        queue!(
+            out,
+            Print(paint(&fit(&title, width), &theme::theme().header))
+        )?;
+
+        for (row, number) in visible.iter().skip(self.offset).take(rows).enumerate() {
+            let Some(task) = self.tasks.get(*number) else {
+                continue;
+            };
+            let head = format!("{number:>3} ");
+            let rest = fit(
+                &format!(" {}  {}", task.name, task.description),
+                width.saturating_sub(head.chars().count() + 1),
+            );
+
+            queue!(out, MoveTo(0, row as u16 + 1))?;
+
+            if self.offset + row == self.selected {
+                let text = format!("{head}{}{rest}", task.completed_symbol());
+
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(format!("{text:<width$}")),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(head), Print(theme::status(task)), Print(rest))?;
+            }
+        }
+
+        if visible.is_empty() {
+            queue!(out, MoveTo(0, 1), Print("No tasks."))?;
+        }
+
+        let line = match &self.mode {
+            Mode::Normal => self.message.clone().unwrap_or_default(),
+            Mode::Filter => format!("Filter: {}", self.filter),
+            Mode::Input { prompt, text } => format!("{}: {text}", prompt.label()),
+            Mode::Confirm { number } => format!(
+                "Remove task {number} {:?}? y/n",
+                self.tasks
+                    .get(*number)
+                    .map_or("", |task| task.name.as_str())
+            ),
+        };
+
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(2) as u16),
+            Print(fit(&line, width)),
+            MoveTo(0, height.saturating_sub(1) as u16),
+            Print(paint(&fit(HELP, width), "dimmed"))
+        )?;
+
+        out.flush()
+    }
+}
+
+/// `same` checks if two tasks have the same name, description and status,
+/// to tell if the task shown is still the saved one.
+fn same(task: &Task, other: &Task) -> bool {
+    task.name == other.name
+        && task.description == other.description
+        && task.get_is_completed() == other.get_is_completed()
+}
+
+/// `fit` cuts a text to the given width, ending it with `…` if it was longer.
+fn fit(text: &str, width: usize) -> String {
+    let text = text.replace(['\t', '\n'], " ");
+
+    if text.chars().count() <= width {
+        return text;
+    }
+
+    let cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{cut}…")
+}
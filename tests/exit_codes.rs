@@ -0,0 +1,179 @@
+//! End to end checks of the exit codes, one per failure class.
+//! Every check runs `todo` over its own configuration and data file in a temporary directory.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde_json::Value;
+
+/// `Sandbox` is a temporary directory with a configuration file, removed once dropped.
+struct Sandbox {
+    dir: PathBuf,
+}
+
+/// `Run` is the exit code and the kind of the JSON error of a run, written on the output.
+struct Run {
+    code: i32,
+    kind: Option<String>,
+}
+
+/// `Sandbox` is an implementation of the temporary directory.
+impl Sandbox {
+    /// `new` creates the directory of a check with a configuration keeping the tasks in it.
+    fn new(name: &str) -> Sandbox {
+        let dir = std::env::temp_dir().join(format!("todo-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let sandbox = Sandbox { dir };
+        sandbox.write(
+            "config.toml",
+            "version = 1\ndata_path_file = \"data.toml\"\n",
+        );
+        sandbox
+    }
+
+    /// `write` writes a file of the directory.
+    fn write(&self, name: &str, content: &str) {
+        fs::write(self.dir.join(name), content).unwrap();
+    }
+
+    /// `run` runs `todo` with JSON output and the given arguments.
+    fn run(&self, args: &[&str]) -> Run {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo"))
+            .current_dir(&self.dir)
+            .env("TODO_CONFIG", self.dir.join("config.toml"))
+            .env("XDG_CONFIG_HOME", &self.dir)
+            .env("XDG_DATA_HOME", &self.dir)
+            .args(["--output", "json"])
+            .args(args)
+            .output()
+            .unwrap();
+
+        let kind = serde_json::from_slice::<Value>(&output.stdout)
+            .ok()
+            .and_then(|error| error["error"]["kind"].as_str().map(String::from));
+
+        Run {
+            code: output.status.code().unwrap(),
+            kind,
+        }
+    }
+
+    /// `expect` runs `todo` and checks its exit code and the kind of its error.
+    fn expect(&self, args: &[&str], code: i32, kind: &str) {
+        let run = self.run(args);
+
+        assert_eq!(run.code, code, "exit code of todo {}", args.join(" "));
+        assert_eq!(
+            run.kind.as_deref(),
+            Some(kind),
+            "error of todo {}",
+            args.join(" ")
+        );
+    }
+}
+
+/// `Drop` is an implementation to remove the directory.
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn success() {
+    let sandbox = Sandbox::new("success");
+
+    assert_eq!(sandbox.run(&["add", "Task"]).code, 0);
+    assert_eq!(sandbox.run(&["toggle", "0"]).code, 0);
+    assert_eq!(sandbox.run(&["list"]).code, 0);
+}
+
+#[test]
+fn not_found() {
+    let sandbox = Sandbox::new("not-found");
+
+    sandbox.expect(&["toggle", "5"], 66, "not_found");
+    sandbox.expect(&["remove", "0"], 66, "not_found");
+    sandbox.expect(&["--list", "nope", "list"], 66, "not_found");
+    sandbox.expect(&["lists", "delete", "nope"], 66, "not_found");
+}
+
+#[test]
+fn invalid_input() {
+    let sandbox = Sandbox::new("invalid-input");
+
+    sandbox.expect(&["toggle", "abc"], 64, "invalid_input");
+    sandbox.expect(&["unknown"], 64, "invalid_input");
+    sandbox.expect(&["lists", "create", "bad name"], 64, "invalid_input");
+    sandbox.expect(&["config", "get", "nope"], 64, "invalid_input");
+}
+
+#[test]
+fn conflict() {
+    let sandbox = Sandbox::new("conflict");
+
+    assert_eq!(sandbox.run(&["lists", "create", "work"]).code, 0);
+    sandbox.expect(&["lists", "create", "work"], 73, "conflict");
+}
+
+#[test]
+fn parse_error() {
+    let sandbox = Sandbox::new("parse-error");
+    sandbox.write("data.toml", "garbage = [");
+
+    sandbox.expect(&["list"], 65, "parse_error");
+}
+
+#[test]
+fn lock_busy() {
+    let sandbox = Sandbox::new("lock-busy");
+    sandbox.write("data.toml.lock", "");
+
+    let lock = fs::File::open(sandbox.dir.join("data.toml.lock")).unwrap();
+    lock.lock().unwrap();
+
+    sandbox.expect(&["add", "Task"], 75, "lock_busy");
+
+    drop(lock);
+    assert_eq!(sandbox.run(&["add", "Task"]).code, 0);
+}
+
+#[test]
+fn stale_lock_file() {
+    let sandbox = Sandbox::new("stale-lock");
+    sandbox.write("data.toml.lock", "4194304");
+
+    assert_eq!(sandbox.run(&["add", "Task"]).code, 0);
+}
+
+#[test]
+fn config_error() {
+    let sandbox = Sandbox::new("config-error");
+    sandbox.write(
+        "config.toml",
+        "version = 1\ndata_path_file = \"data.toml\"\nstorage = \"bogus\"\n",
+    );
+
+    sandbox.expect(&["list"], 78, "config_error");
+}
+
+#[test]
+fn malformed_config_option() {
+    let sandbox = Sandbox::new("config-option");
+
+    sandbox.expect(&["-c", "banner", "list"], 78, "config_error");
+    assert_eq!(sandbox.run(&["-c", "banner=false", "list"]).code, 0);
+}
+
+#[test]
+fn io_error() {
+    let sandbox = Sandbox::new("io-error");
+    assert_eq!(sandbox.run(&["add", "Task"]).code, 0);
+    fs::create_dir(sandbox.dir.join("data.toml.trash")).unwrap();
+
+    sandbox.expect(&["remove", "0"], 74, "io_error");
+    assert_eq!(sandbox.run(&["list"]).code, 0);
+}
@@ -0,0 +1,53 @@
+//! The build tasks of todo, run with `cargo xtask <task>`.
+//!
+//! - `man [DIR]` builds todo and writes its manual pages to `DIR`, `target/man` by default.
+//!   The pages come from `todo man --out-dir`, so they are rendered by `clap_mangen`
+//!   from the same command line definition, examples and exit codes as `todo man`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+fn main() {
+    let mut args = env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("man") => man(args.next().map(PathBuf::from)),
+        _ => {
+            eprintln!("Usage: cargo xtask man [DIR]");
+            process::exit(64);
+        }
+    }
+}
+
+/// `man` writes the manual pages of todo to a directory.
+fn man(dir: Option<PathBuf>) {
+    let root = workspace_root();
+    let dir = dir.unwrap_or_else(|| root.join("target").join("man"));
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+
+    let status = Command::new(cargo)
+        .current_dir(&root)
+        .args(["run", "--quiet", "--package", "todo", "--"])
+        .args(["man", "--out-dir"])
+        .arg(&dir)
+        .stdout(Stdio::null())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => println!("Manual pages written to {}", dir.display()),
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(err) => {
+            eprintln!("Cannot run cargo, {err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// `workspace_root` returns the directory of the workspace, the parent of the xtask package.
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}